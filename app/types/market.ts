@@ -63,6 +63,7 @@ export interface EncryptedPoolState {
   stateVersion: BN;
   lastComputationId: BN;
   pendingComputations: number;
+  batchComputationId: BN;
   lastUpdated: BN;
   bump: number;
   isInitialized: boolean;
@@ -133,6 +134,63 @@ export const CONTRACT_ERRORS = {
   6027: "Invalid computation result",
   6028: "Arithmetic overflow",
   6029: "Arithmetic underflow",
+  6030: "Market index page is full",
+  6031: "Market is not listed in this index page",
+  6032: "Portfolio is full and has no settled positions to evict",
+  6033: "Bet limits must satisfy 0 < min_bet <= max_bet <= max_pool",
+  6034: "Bet amount is below the market minimum",
+  6035: "Bet amount exceeds the market maximum",
+  6036: "Bet would exceed the market's maximum pool size",
+  6037: "Exit fee exceeds the maximum allowed",
+  6038: "Creator seed liquidity cannot be withdrawn",
+  6039: "Position has not been processed yet",
+  6040: "No fees available to collect",
+  6041: "Listing price must be greater than zero",
+  6042: "Listing does not match this position",
+  6043: "Position cannot be transferred to its current owner",
+  6044: "Position asset does not match the position",
+  6045: "Odds reveal interval cannot be negative",
+  6046: "Odds snapshots are disabled for this market",
+  6047: "Odds reveal interval has not elapsed",
+  6048: "Pending bet queue is full",
+  6049: "No pending bets to process",
+  6050: "Position accounts do not match the pending bet queue",
+  6051: "Payout batch must contain between one and four positions",
+  6052: "Token account is not an allowed payout destination",
+  6053: "Outflow would exceed the market's deposits",
+  6054: "Callback computation does not match the one queued for this position",
+  6055: "Bet's computation still matches the current pool state",
+  6056: "Audit batch must contain between 1 and AUDIT_BATCH_SIZE positions",
+  6057: "Position has already been counted in the audit",
+  6058: "An audit computation is already in flight",
+  6059: "Audit has already been attested",
+  6060: "No positions have been folded into the audit",
+  6061: "Position has no processed stake to disclose",
+  6062: "Audit record must be supplied exactly when the market has an auditor",
+  6063: "Markets with an auditor only accept bets through place_bet",
+  6064: "Market does not allow positions to be revealed",
+  6065: "Position has already been revealed",
+  6066: "Market does not use private payouts",
+  6067: "Payouts on this market can only be revealed by the position holder",
+  6068: "Instruction is not available for this market kind",
+  6069: "Revealed bet does not match the commitment",
+  6070: "Reveal window has not ended",
+  6071: "Fixed odds must be between 1x and 100x",
+  6072: "Instruction requires a fixed-odds market",
+  6073: "Instruction requires a parimutuel market",
+  6074: "House funding cannot cover the maximum liability",
+  6075: "Position has a computation in flight",
+  6076: "Bet is still waiting in the pending queue",
+  6077: "Audit has not counted every pooled position",
+  6078: "Reveal window has closed",
+  6079: "Unrevealed bets must be forfeited before payouts",
+  6080: "Every pooled position needs a computed payout first",
+  6081: "No house funds left to sweep",
+  6082: "A bet batch is already in flight",
+  6083: "A claim withdrawal is in flight on this page",
+  6084: "Claim page is still being filled",
+  6085: "Claim page belongs to a different market",
+  6086: "A private payout computation is already in flight",
 } as const;
 
 export function toMarketDisplay(
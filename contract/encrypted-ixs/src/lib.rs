@@ -9,58 +9,422 @@ mod circuits {
         pub amount: u64,
     }
 
-    pub struct ProcessBetResult {
-        pub validated_outcome: u8,
-        pub validated_amount: u64,
-        pub success: u8,
+    pub struct SeedLiquidityInput {
+        pub yes_amount: u64,
+        pub no_amount: u64,
     }
 
-    pub struct ComputePayoutInput {
-        pub user_outcome: u8,
-        pub user_amount: u64,
-        pub winning_outcome: u8,
-        pub winning_pool: u64,
-        pub losing_pool: u64,
+    pub struct PoolTotals {
+        pub yes_pool: u64,
+        pub no_pool: u64,
     }
 
-    pub struct PayoutResult {
-        pub payout: u64,
+    pub struct PositionStake {
+        pub yes_amount: u64,
+        pub no_amount: u64,
+    }
+
+    pub struct ClaimNote {
+        pub secret: u128,
+        pub balance: u64,
+    }
+
+    pub struct PrivatePayoutTotals {
+        pub payouts: u64,
+        pub winnings: u64,
     }
 
     #[instruction]
-    pub fn process_bet(input: Enc<Shared, ProcessBetInput>) -> Enc<Shared, ProcessBetResult> {
-        let bet = input.to_arcis();
+    pub fn seed_liquidity(
+        input: Enc<Shared, SeedLiquidityInput>,
+        seed_amount: u64,
+    ) -> (Enc<Mxe, PoolTotals>, Enc<Mxe, PositionStake>) {
+        let seed = input.to_arcis();
+
+        // A split that doesn't add up to the deposit falls back to an even split
+        let valid_split = (seed.yes_amount as u128) + (seed.no_amount as u128) == seed_amount as u128;
+        let even_yes = seed_amount / 2;
+
+        let stake = PositionStake {
+            yes_amount: if valid_split { seed.yes_amount } else { even_yes },
+            no_amount: if valid_split { seed.no_amount } else { seed_amount - even_yes },
+        };
+
+        let totals = PoolTotals {
+            yes_pool: stake.yes_amount,
+            no_pool: stake.no_amount,
+        };
+
+        (Mxe::get().from_arcis(totals), Mxe::get().from_arcis(stake))
+    }
 
+    fn stake_for_bet(bet: ProcessBetInput, deposit_amount: u64, min_bet: u64, max_bet: u64) -> PositionStake {
         let valid_outcome = if bet.outcome > 1 { 0u8 } else { 1u8 };
-        let valid_amount = if bet.amount == 0 { 0u8 } else { 1u8 };
+        let valid_amount = if bet.amount == 0 || bet.amount < min_bet || bet.amount > max_bet || bet.amount > deposit_amount {
+            0u8
+        } else {
+            1u8
+        };
         let success = if valid_outcome == 1 && valid_amount == 1 { 1u8 } else { 0u8 };
 
-        let result = ProcessBetResult {
-            validated_outcome: if success == 1 { bet.outcome } else { 0 },
-            validated_amount: if success == 1 { bet.amount } else { 0 },
-            success,
+        let staked = if success == 1 { bet.amount } else { 0 };
+        PositionStake {
+            yes_amount: if bet.outcome == 1 { staked } else { 0 },
+            no_amount: if bet.outcome == 0 { staked } else { 0 },
+        }
+    }
+
+    #[instruction]
+    pub fn process_bet(
+        input: Enc<Shared, ProcessBetInput>,
+        pool: Enc<Mxe, PoolTotals>,
+        empty_pool: u8,
+        deposit_amount: u64,
+        min_bet: u64,
+        max_bet: u64,
+        auditor: Shared,
+    ) -> (Enc<Mxe, PoolTotals>, Enc<Mxe, PositionStake>, Enc<Shared, PositionStake>) {
+        let mut totals = pool.to_arcis();
+
+        // A market opened without seed liquidity has no pool ciphertext yet; zeros stand in for it
+        if empty_pool == 1 {
+            totals.yes_pool = 0;
+            totals.no_pool = 0;
+        }
+
+        let stake = stake_for_bet(input.to_arcis(), deposit_amount, min_bet, max_bet);
+
+        totals.yes_pool += stake.yes_amount;
+        totals.no_pool += stake.no_amount;
+
+        let audit_stake = PositionStake {
+            yes_amount: stake.yes_amount,
+            no_amount: stake.no_amount,
         };
 
-        input.owner.from_arcis(result)
+        (pool.owner.from_arcis(totals), Mxe::get().from_arcis(stake), auditor.from_arcis(audit_stake))
     }
 
+    // Arcis takes each batch slot as its own argument and output
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
     #[instruction]
-    pub fn compute_payout(input: Enc<Shared, ComputePayoutInput>) -> Enc<Shared, PayoutResult> {
-        let data = input.to_arcis();
+    pub fn process_bet_batch(
+        bet_0: Enc<Shared, ProcessBetInput>,
+        bet_1: Enc<Shared, ProcessBetInput>,
+        bet_2: Enc<Shared, ProcessBetInput>,
+        bet_3: Enc<Shared, ProcessBetInput>,
+        pool: Enc<Mxe, PoolTotals>,
+        empty_pool: u8,
+        deposit_0: u64,
+        deposit_1: u64,
+        deposit_2: u64,
+        deposit_3: u64,
+        min_bet: u64,
+        max_bet: u64,
+        count: u8,
+    ) -> (
+        Enc<Mxe, PoolTotals>,
+        Enc<Mxe, PositionStake>,
+        Enc<Mxe, PositionStake>,
+        Enc<Mxe, PositionStake>,
+        Enc<Mxe, PositionStake>,
+    ) {
+        let mut totals = pool.to_arcis();
+
+        if empty_pool == 1 {
+            totals.yes_pool = 0;
+            totals.no_pool = 0;
+        }
+
+        let stakes = [
+            stake_for_bet(bet_0.to_arcis(), deposit_0, min_bet, max_bet),
+            stake_for_bet(bet_1.to_arcis(), deposit_1, min_bet, max_bet),
+            stake_for_bet(bet_2.to_arcis(), deposit_2, min_bet, max_bet),
+            stake_for_bet(bet_3.to_arcis(), deposit_3, min_bet, max_bet),
+        ];
 
-        let user_won = if data.user_outcome == data.winning_outcome { 1u8 } else { 0u8 };
+        // Unused slots are padded on-chain with a copy of the first bet and masked out here
+        for (i, stake) in stakes.iter().enumerate() {
+            if (i as u8) < count {
+                totals.yes_pool += stake.yes_amount;
+                totals.no_pool += stake.no_amount;
+            }
+        }
 
-        let payout = if user_won == 1 && data.winning_pool > 0 {
-            let share = (data.user_amount * data.losing_pool) / data.winning_pool;
-            data.user_amount + share
-        } else if user_won == 1 {
-            data.user_amount
+        let [stake_0, stake_1, stake_2, stake_3] = stakes;
+        (
+            pool.owner.from_arcis(totals),
+            Mxe::get().from_arcis(stake_0),
+            Mxe::get().from_arcis(stake_1),
+            Mxe::get().from_arcis(stake_2),
+            Mxe::get().from_arcis(stake_3),
+        )
+    }
+
+    // Arcis has no saturating_sub, so the underflow guard is an explicit branch
+    #[allow(clippy::implicit_saturating_sub)]
+    #[instruction]
+    pub fn withdraw_bet(
+        stake: Enc<Mxe, PositionStake>,
+        pool: Enc<Mxe, PoolTotals>,
+    ) -> Enc<Mxe, PoolTotals> {
+        let stake = stake.to_arcis();
+        let mut totals = pool.to_arcis();
+
+        // Both sides are always updated so the withdrawn direction stays hidden
+        totals.yes_pool = if totals.yes_pool >= stake.yes_amount { totals.yes_pool - stake.yes_amount } else { 0 };
+        totals.no_pool = if totals.no_pool >= stake.no_amount { totals.no_pool - stake.no_amount } else { 0 };
+
+        pool.owner.from_arcis(totals)
+    }
+
+    #[instruction]
+    pub fn transfer_position(
+        receiver: Shared,
+        stake: Enc<Mxe, PositionStake>,
+    ) -> Enc<Shared, PositionStake> {
+        let stake = stake.to_arcis();
+
+        receiver.from_arcis(stake)
+    }
+
+    #[instruction]
+    pub fn disclose_position(
+        viewer: Shared,
+        stake: Enc<Mxe, PositionStake>,
+    ) -> Enc<Shared, PositionStake> {
+        let stake = stake.to_arcis();
+
+        viewer.from_arcis(stake)
+    }
+
+    #[instruction]
+    pub fn reveal_position(stake: Enc<Mxe, PositionStake>) -> (u8, u64) {
+        let stake = stake.to_arcis();
+
+        let outcome = if stake.yes_amount > 0 { 1u8 } else { 0u8 };
+        let amount = stake.yes_amount + stake.no_amount;
+
+        (outcome.reveal(), amount.reveal())
+    }
+
+    #[instruction]
+    pub fn reveal_odds_snapshot(pool: Enc<Mxe, PoolTotals>) -> u16 {
+        let totals = pool.to_arcis();
+
+        let total = (totals.yes_pool as u128) + (totals.no_pool as u128);
+        let denominator = if total == 0 { 1u128 } else { total };
+
+        // Rounded to the nearest 5% so a single bet can't be read off the delta
+        let buckets = ((totals.yes_pool as u128) * 40 + denominator) / (2 * denominator);
+        let yes_share_bps = if total == 0 { 5_000u16 } else { (buckets as u16) * 500 };
+
+        yes_share_bps.reveal()
+    }
+
+    fn payout_for(
+        stake: PositionStake,
+        yes_pool: u64,
+        no_pool: u64,
+        winning_outcome: u8,
+        yes_odds_bps: u64,
+        no_odds_bps: u64,
+    ) -> u64 {
+        let (user_amount, winning_pool, losing_pool, odds_bps) = if winning_outcome == 1 {
+            (stake.yes_amount, yes_pool, no_pool, yes_odds_bps)
+        } else {
+            (stake.no_amount, no_pool, yes_pool, no_odds_bps)
+        };
+
+        // Odds locked at bet time pay out of the house vault instead of the losing pool
+        if odds_bps > 0 {
+            (((user_amount as u128) * (odds_bps as u128)) / 10_000u128) as u64
+        } else if winning_pool > 0 {
+            let share = ((user_amount as u128) * (losing_pool as u128)) / (winning_pool as u128);
+            user_amount + share as u64
         } else {
             0u64
+        }
+    }
+
+    #[instruction]
+    pub fn compute_payout(
+        stake: Enc<Mxe, PositionStake>,
+        pool: Enc<Mxe, PoolTotals>,
+        winning_outcome: u8,
+        yes_odds_bps: u64,
+        no_odds_bps: u64,
+    ) -> u64 {
+        let totals = pool.to_arcis();
+
+        let payout = payout_for(
+            stake.to_arcis(),
+            totals.yes_pool,
+            totals.no_pool,
+            winning_outcome,
+            yes_odds_bps,
+            no_odds_bps,
+        );
+
+        payout.reveal()
+    }
+
+    // Winnings are guarded with an explicit branch since Arcis has no saturating_sub
+    #[allow(clippy::too_many_arguments, clippy::type_complexity, clippy::implicit_saturating_sub)]
+    #[instruction]
+    pub fn compute_private_payout(
+        secret: Enc<Shared, u128>,
+        stake: Enc<Mxe, PositionStake>,
+        pool: Enc<Mxe, PoolTotals>,
+        running: Enc<Mxe, PrivatePayoutTotals>,
+        first_payout: u8,
+        last_payout: u8,
+        deposit_amount: u64,
+        winning_outcome: u8,
+        yes_odds_bps: u64,
+        no_odds_bps: u64,
+    ) -> (Enc<Shared, u64>, Enc<Mxe, ClaimNote>, Enc<Mxe, PrivatePayoutTotals>, u64, u64) {
+        let totals = pool.to_arcis();
+        let mut sums = running.to_arcis();
+
+        // The first payout has no running sum yet; the pool ciphertext is passed as a placeholder
+        if first_payout == 1 {
+            sums.payouts = 0;
+            sums.winnings = 0;
+        }
+
+        let payout = payout_for(
+            stake.to_arcis(),
+            totals.yes_pool,
+            totals.no_pool,
+            winning_outcome,
+            yes_odds_bps,
+            no_odds_bps,
+        );
+
+        sums.payouts += payout;
+        sums.winnings += if payout > deposit_amount { payout - deposit_amount } else { 0 };
+
+        // Only the market-wide sums are ever revealed, once the last note is written
+        let revealed_payouts = if last_payout == 1 { sums.payouts } else { 0 };
+        let revealed_winnings = if last_payout == 1 { sums.winnings } else { 0 };
+
+        let note = ClaimNote {
+            secret: secret.to_arcis(),
+            balance: payout,
         };
 
-        let result = PayoutResult { payout };
+        (
+            secret.owner.from_arcis(payout),
+            Mxe::get().from_arcis(note),
+            Mxe::get().from_arcis(sums),
+            revealed_payouts.reveal(),
+            revealed_winnings.reveal(),
+        )
+    }
+
+    #[allow(clippy::type_complexity)]
+    #[instruction]
+    pub fn withdraw_private_claim(
+        secret: Enc<Shared, u128>,
+        note_0: Enc<Mxe, ClaimNote>,
+        note_1: Enc<Mxe, ClaimNote>,
+        note_2: Enc<Mxe, ClaimNote>,
+        note_3: Enc<Mxe, ClaimNote>,
+        filled: u8,
+        amount: u64,
+    ) -> (Enc<Mxe, ClaimNote>, Enc<Mxe, ClaimNote>, Enc<Mxe, ClaimNote>, Enc<Mxe, ClaimNote>, u64) {
+        let claim = secret.to_arcis();
+        let mut notes = [note_0.to_arcis(), note_1.to_arcis(), note_2.to_arcis(), note_3.to_arcis()];
+
+        // Every note is rewritten so the debited slot can't be told apart from the rest,
+        // and an uncovered request withdraws nothing rather than revealing a balance
+        // Arcis has no shifts, so each slot's bit in the filled mask is read by division
+        let slot_bits = [1u8, 2u8, 4u8, 8u8];
+        let mut debited = false;
+        for (note, bit) in notes.iter_mut().zip(slot_bits.iter()) {
+            let live = (filled / *bit) % 2 == 1;
+            let hit = live && !debited && note.secret == claim && note.balance >= amount;
+            note.balance = if hit { note.balance - amount } else { note.balance };
+            debited = debited || hit;
+        }
+        let approved = if debited { amount } else { 0 };
+
+        let [note_0, note_1, note_2, note_3] = notes;
+        (
+            Mxe::get().from_arcis(note_0),
+            Mxe::get().from_arcis(note_1),
+            Mxe::get().from_arcis(note_2),
+            Mxe::get().from_arcis(note_3),
+            approved.reveal(),
+        )
+    }
+
+    #[instruction]
+    pub fn compute_payouts_batch(
+        stake_0: Enc<Mxe, PositionStake>,
+        stake_1: Enc<Mxe, PositionStake>,
+        stake_2: Enc<Mxe, PositionStake>,
+        stake_3: Enc<Mxe, PositionStake>,
+        pool: Enc<Mxe, PoolTotals>,
+        winning_outcome: u8,
+        count: u8,
+    ) -> (u64, u64, u64, u64) {
+        let totals = pool.to_arcis();
+
+        let mut payouts = [
+            payout_for(stake_0.to_arcis(), totals.yes_pool, totals.no_pool, winning_outcome, 0, 0),
+            payout_for(stake_1.to_arcis(), totals.yes_pool, totals.no_pool, winning_outcome, 0, 0),
+            payout_for(stake_2.to_arcis(), totals.yes_pool, totals.no_pool, winning_outcome, 0, 0),
+            payout_for(stake_3.to_arcis(), totals.yes_pool, totals.no_pool, winning_outcome, 0, 0),
+        ];
+
+        // Padded slots are zeroed so they never reveal a copy of the first payout
+        for (i, payout) in payouts.iter_mut().enumerate() {
+            if (i as u8) >= count {
+                *payout = 0;
+            }
+        }
+
+        (payouts[0].reveal(), payouts[1].reveal(), payouts[2].reveal(), payouts[3].reveal())
+    }
+
+    #[instruction]
+    pub fn audit_pool_chunk(
+        stake_0: Enc<Mxe, PositionStake>,
+        stake_1: Enc<Mxe, PositionStake>,
+        stake_2: Enc<Mxe, PositionStake>,
+        stake_3: Enc<Mxe, PositionStake>,
+        running: Enc<Mxe, PoolTotals>,
+        count: u8,
+        first_chunk: u8,
+    ) -> Enc<Mxe, PoolTotals> {
+        let mut totals = running.to_arcis();
+
+        // The first chunk has no running sum yet; the pool ciphertext is passed as a placeholder
+        if first_chunk == 1 {
+            totals.yes_pool = 0;
+            totals.no_pool = 0;
+        }
+
+        let stakes = [stake_0.to_arcis(), stake_1.to_arcis(), stake_2.to_arcis(), stake_3.to_arcis()];
+        for (i, stake) in stakes.iter().enumerate() {
+            if (i as u8) < count {
+                totals.yes_pool += stake.yes_amount;
+                totals.no_pool += stake.no_amount;
+            }
+        }
+
+        Mxe::get().from_arcis(totals)
+    }
+
+    #[instruction]
+    pub fn attest_pool_audit(running: Enc<Mxe, PoolTotals>, pool: Enc<Mxe, PoolTotals>) -> bool {
+        let sum = running.to_arcis();
+        let totals = pool.to_arcis();
+
+        let consistent = sum.yes_pool == totals.yes_pool && sum.no_pool == totals.no_pool;
 
-        input.owner.from_arcis(result)
+        consistent.reveal()
     }
 }
@@ -2,7 +2,32 @@ pub const MARKET_SEED: &[u8] = b"dark_market";
 pub const POOL_STATE_SEED: &[u8] = b"pool_state";
 pub const USER_POSITION_SEED: &[u8] = b"position";
 pub const VAULT_SEED: &[u8] = b"vault";
+pub const REGISTRY_SEED: &[u8] = b"market_registry";
+pub const MARKET_INDEX_SEED: &[u8] = b"market_index";
+pub const PORTFOLIO_SEED: &[u8] = b"portfolio";
+pub const CREATOR_POSITION_SEED: &[u8] = b"creator_position";
+pub const LISTING_SEED: &[u8] = b"listing";
+pub const POSITION_ASSET_SEED: &[u8] = b"position_asset";
+pub const PENDING_BETS_SEED: &[u8] = b"pending_bets";
+pub const POOL_AUDIT_SEED: &[u8] = b"pool_audit";
+pub const AUDIT_RECORD_SEED: &[u8] = b"audit_record";
+pub const CLAIM_PAGE_SEED: &[u8] = b"claim_page";
 
 pub const MAX_QUESTION_LEN: usize = 200;
 pub const ENCRYPTED_BET_SIZE: usize = 64;
 pub const ENCRYPTED_STATE_SIZE: usize = 256;
+pub const MARKETS_PER_INDEX_PAGE: usize = 256;
+pub const MAX_PORTFOLIO_ENTRIES: usize = 64;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const MAX_EXIT_FEE_BPS: u16 = 1_000;
+pub const CRANK_FEE_BPS: u64 = 10;
+pub const ODDS_SNAPSHOT_HISTORY: usize = 8;
+pub const BET_BATCH_SIZE: usize = 4;
+pub const MAX_PENDING_BETS: usize = 64;
+// Every slot is a full payout circuit, so larger batches exceed the MPC circuit size;
+// cranks call compute_payouts_batch repeatedly until payouts_computed reaches pooled_positions
+pub const PAYOUT_BATCH_SIZE: usize = 4;
+pub const AUDIT_BATCH_SIZE: usize = 4;
+// Every note on a page is rescanned on each withdrawal, so the page size is the anonymity set
+pub const CLAIM_PAGE_SLOTS: usize = 4;
+pub const MAX_FIXED_ODDS_BPS: u32 = 1_000_000;
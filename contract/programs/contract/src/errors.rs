@@ -91,4 +91,175 @@ pub enum DarkPoolError {
 
     #[msg("Arithmetic underflow")]
     Underflow,
+
+    #[msg("Market index page is full")]
+    MarketIndexPageFull,
+
+    #[msg("Market is not listed in this index page")]
+    MarketNotIndexed,
+
+    #[msg("Portfolio is full and has no settled positions to evict")]
+    PortfolioFull,
+
+    #[msg("Bet limits must satisfy 0 < min_bet <= max_bet <= max_pool")]
+    InvalidBetLimits,
+
+    #[msg("Bet amount is below the market minimum")]
+    BetBelowMinimum,
+
+    #[msg("Bet amount exceeds the market maximum")]
+    BetAboveMaximum,
+
+    #[msg("Bet would exceed the market's maximum pool size")]
+    PoolCapExceeded,
+
+    #[msg("Exit fee exceeds the maximum allowed")]
+    ExitFeeTooHigh,
+
+    #[msg("Creator seed liquidity cannot be withdrawn")]
+    SeedPositionLocked,
+
+    #[msg("Position has not been processed yet")]
+    PositionNotProcessed,
+
+    #[msg("No fees available to collect")]
+    NoFeesToCollect,
+
+    #[msg("Listing price must be greater than zero")]
+    InvalidListingPrice,
+
+    #[msg("Listing does not match this position")]
+    InvalidListing,
+
+    #[msg("Position cannot be transferred to its current owner")]
+    InvalidTransferRecipient,
+
+    #[msg("Position asset does not match the position")]
+    InvalidPositionAsset,
+
+    #[msg("Odds reveal interval cannot be negative")]
+    InvalidOddsRevealInterval,
+
+    #[msg("Odds snapshots are disabled for this market")]
+    OddsRevealDisabled,
+
+    #[msg("Odds reveal interval has not elapsed")]
+    OddsRevealTooSoon,
+
+    #[msg("Pending bet queue is full")]
+    PendingQueueFull,
+
+    #[msg("No pending bets to process")]
+    PendingQueueEmpty,
+
+    #[msg("Position accounts do not match the pending bet queue")]
+    PendingBetMismatch,
+
+    #[msg("Payout batch must contain between one and four positions")]
+    InvalidPayoutBatch,
+
+    #[msg("Token account is not an allowed payout destination")]
+    InvalidPayoutDestination,
+
+    #[msg("Outflow would exceed the market's deposits")]
+    SolvencyViolation,
+
+    #[msg("Callback computation does not match the one queued for this position")]
+    ComputationMismatch,
+
+    #[msg("Bet's computation still matches the current pool state")]
+    BetNotStale,
+
+    #[msg("Audit batch must contain between 1 and AUDIT_BATCH_SIZE positions")]
+    InvalidAuditBatch,
+
+    #[msg("Position has already been counted in the audit")]
+    PositionAlreadyAudited,
+
+    #[msg("An audit computation is already in flight")]
+    AuditInProgress,
+
+    #[msg("Audit has already been attested")]
+    AuditAlreadyAttested,
+
+    #[msg("No positions have been folded into the audit")]
+    AuditEmpty,
+
+    #[msg("Position has no processed stake to disclose")]
+    NothingToDisclose,
+
+    #[msg("Audit record must be supplied exactly when the market has an auditor")]
+    AuditRecordMismatch,
+
+    #[msg("Markets with an auditor only accept bets through place_bet")]
+    AuditedMarketRequiresPlaceBet,
+
+    #[msg("Market does not allow positions to be revealed")]
+    PositionRevealDisabled,
+
+    #[msg("Position has already been revealed")]
+    PositionAlreadyRevealed,
+
+    #[msg("Market does not use private payouts")]
+    PrivatePayoutsDisabled,
+
+    #[msg("Payouts on this market can only be revealed by the position holder")]
+    PrivatePayoutMarket,
+
+    #[msg("Instruction is not available for this market kind")]
+    InvalidMarketKind,
+
+    #[msg("Revealed bet does not match the commitment")]
+    CommitmentMismatch,
+
+    #[msg("Reveal window has not ended")]
+    RevealWindowOpen,
+
+    #[msg("Fixed odds must be between 1x and 100x")]
+    InvalidOdds,
+
+    #[msg("Instruction requires a fixed-odds market")]
+    FixedOddsRequired,
+
+    #[msg("Instruction requires a parimutuel market")]
+    ParimutuelRequired,
+
+    #[msg("House funding cannot cover the maximum liability")]
+    HouseLiabilityExceeded,
+
+    #[msg("Position has a computation in flight")]
+    ComputationInFlight,
+
+    #[msg("Bet is still waiting in the pending queue")]
+    BetStillQueued,
+
+    #[msg("Audit has not counted every pooled position")]
+    AuditIncomplete,
+
+    #[msg("Reveal window has closed")]
+    RevealWindowClosed,
+
+    #[msg("Unrevealed bets must be forfeited before payouts")]
+    UnrevealedBetsPending,
+
+    #[msg("Every pooled position needs a computed payout first")]
+    PayoutsOutstanding,
+
+    #[msg("No house funds left to sweep")]
+    NothingToSweep,
+
+    #[msg("A bet batch is already in flight")]
+    BatchInFlight,
+
+    #[msg("A claim withdrawal is in flight on this page")]
+    ClaimInFlight,
+
+    #[msg("Claim page is still being filled")]
+    ClaimPageOpen,
+
+    #[msg("Claim page belongs to a different market")]
+    InvalidClaimPage,
+
+    #[msg("A private payout computation is already in flight")]
+    PrivatePayoutInFlight,
 }
@@ -1,18 +1,31 @@
 use anchor_lang::prelude::*;
+use crate::state::{MarketKind, PricingMode};
 
 #[event]
 pub struct MarketCreated {
     pub market: Pubkey,
     pub market_id: u64,
+    pub creator_nonce: u64,
     pub authority: Pubkey,
     pub token_mint: Pubkey,
     pub betting_end_ts: i64,
     pub resolution_end_ts: i64,
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub max_pool: u64,
+    pub odds_reveal_interval: i64,
+    pub auditor_pubkey: Option<[u8; 32]>,
+    pub reveal_after_resolution: bool,
+    pub private_payouts: bool,
+    pub kind: MarketKind,
+    pub pricing_mode: PricingMode,
 }
 
 #[event]
 pub struct MarketOpened {
     pub market: Pubkey,
+    pub seed_position: Pubkey,
+    pub seed_amount: u64,
     pub opened_at: i64,
 }
 
@@ -22,6 +35,47 @@ pub struct BetPlaced {
     pub position: Pubkey,
     pub bettor: Pubkey,
     pub deposit_amount: u64,
+    pub state_version: u64,
+    pub computation_id: u64,
+}
+
+#[event]
+pub struct BetCommitted {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub bettor: Pubkey,
+    pub deposit_amount: u64,
+    pub commitment: [u8; 32],
+}
+
+#[event]
+pub struct BetRevealed {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub outcome: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BetForfeited {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub deposit_amount: u64,
+}
+
+#[event]
+pub struct BetQueued {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub bettor: Pubkey,
+    pub deposit_amount: u64,
+    pub queue_length: u32,
+}
+
+#[event]
+pub struct BetBatchQueued {
+    pub market: Pubkey,
+    pub positions: Vec<Pubkey>,
     pub computation_id: u64,
 }
 
@@ -33,6 +87,106 @@ pub struct BetProcessed {
     pub new_commitment: [u8; 32],
 }
 
+#[event]
+pub struct BetBatchAborted {
+    pub market: Pubkey,
+    pub positions: Vec<Pubkey>,
+    pub computation_id: u64,
+}
+
+#[event]
+pub struct PoolStateConflict {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub read_version: u64,
+    pub current_version: u64,
+}
+
+#[event]
+pub struct BetWithdrawalRequested {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub bettor: Pubkey,
+    pub computation_id: u64,
+}
+
+#[event]
+pub struct BetWithdrawn {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub bettor: Pubkey,
+    pub refund_amount: u64,
+    pub exit_fee: u64,
+    pub state_version: u64,
+}
+
+#[event]
+pub struct BetWithdrawalAborted {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub computation_id: u64,
+}
+
+#[event]
+pub struct PositionListed {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub seller: Pubkey,
+    pub price: u64,
+}
+
+#[event]
+pub struct ListingCancelled {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub seller: Pubkey,
+}
+
+#[event]
+pub struct PositionTransferred {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub price: u64,
+    pub computation_id: u64,
+}
+
+#[event]
+pub struct PositionTransferAborted {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub computation_id: u64,
+}
+
+#[event]
+pub struct PositionDisclosureRequested {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub viewer_pubkey: [u8; 32],
+    pub computation_id: u64,
+}
+
+#[event]
+pub struct PositionDisclosed {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub computation: Pubkey,
+    pub nonce: u128,
+    pub encrypted_stake: [[u8; 32]; 2],
+}
+
+#[event]
+pub struct PositionRevealed {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub outcome: u8,
+    pub amount: u64,
+}
+
 #[event]
 pub struct BettingClosed {
     pub market: Pubkey,
@@ -55,6 +209,35 @@ pub struct PayoutComputed {
     pub payout_amount: u64,
 }
 
+#[event]
+pub struct PayoutBatchProcessed {
+    pub market: Pubkey,
+    pub payouts_computed: u32,
+    pub pooled_positions: u32,
+}
+
+#[event]
+pub struct PrivatePayoutComputed {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub claim_page: u32,
+    pub nonce: u128,
+    pub encrypted_payout: [u8; 32],
+}
+
+#[event]
+pub struct PrivateClaimWithdrawn {
+    pub market: Pubkey,
+    pub claim_page: u32,
+}
+
+#[event]
+pub struct PayoutComputationAborted {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub computation_id: u64,
+}
+
 #[event]
 pub struct PayoutClaimed {
     pub market: Pubkey,
@@ -77,10 +260,79 @@ pub struct RefundClaimed {
     pub amount: u64,
 }
 
+#[event]
+pub struct PayoutRoutingUpdated {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub payout_destination: Option<Pubkey>,
+    pub delegate: Option<Pubkey>,
+}
+
+#[event]
+pub struct FeesCollected {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct OddsSnapshot {
+    pub market: Pubkey,
+    pub yes_share_bps: u16,
+    pub state_version: u64,
+    pub revealed_at: i64,
+}
+
+#[event]
+pub struct MarketIntegrityAttested {
+    pub market: Pubkey,
+    pub consistent: bool,
+    pub positions_counted: u32,
+    pub state_version: u64,
+    pub state_commitment: [u8; 32],
+    pub attested_at: i64,
+}
+
 #[event]
 pub struct PoolStateUpdated {
     pub market: Pubkey,
     pub state_version: u64,
+    pub nonce: u128,
+    pub encrypted_state: Vec<u8>,
+    pub previous_commitment: [u8; 32],
     pub commitment: [u8; 32],
     pub updated_at: i64,
 }
+
+#[event]
+pub struct HouseFunded {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub house_funding: u64,
+}
+
+#[event]
+pub struct HouseFundsWithdrawn {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub house_funding: u64,
+    pub house_liability: u64,
+}
+
+#[event]
+pub struct HouseFundsSwept {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub unclaimed_payouts: u64,
+}
+
+#[event]
+pub struct FixedOddsUpdated {
+    pub market: Pubkey,
+    pub yes_odds_bps: u32,
+    pub no_odds_bps: u32,
+}
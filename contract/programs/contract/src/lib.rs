@@ -1,15 +1,19 @@
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 use arcium_anchor::prelude::*;
+use arcium_client::idl::arcium::types::CallbackAccount;
 
 pub mod constants;
 pub mod errors;
 pub mod events;
+pub mod nft;
 pub mod state;
 
 use constants::*;
 use errors::DarkPoolError;
 use events::*;
+use nft::*;
 use state::*;
 
 declare_id!("JAycaSPgFD8hd4Ys7RuJ5pJFzBL8pf11BT8z5HMa1zhZ");
@@ -34,24 +38,122 @@ pub mod contract {
         Ok(())
     }
 
+    pub fn init_seed_liquidity_comp_def(ctx: Context<InitSeedLiquidityCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_withdraw_bet_comp_def(ctx: Context<InitWithdrawBetCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_transfer_position_comp_def(ctx: Context<InitTransferPositionCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_disclose_position_comp_def(ctx: Context<InitDisclosePositionCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_reveal_position_comp_def(ctx: Context<InitRevealPositionCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_reveal_odds_snapshot_comp_def(ctx: Context<InitRevealOddsSnapshotCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_process_bet_batch_comp_def(ctx: Context<InitProcessBetBatchCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_compute_private_payout_comp_def(ctx: Context<InitComputePrivatePayoutCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_compute_payouts_batch_comp_def(ctx: Context<InitComputePayoutsBatchCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_audit_pool_chunk_comp_def(ctx: Context<InitAuditPoolChunkCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_attest_pool_audit_comp_def(ctx: Context<InitAttestPoolAuditCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_withdraw_private_claim_comp_def(ctx: Context<InitWithdrawPrivateClaimCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn initialize_registry(ctx: Context<InitializeRegistry>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.next_market_id = 0;
+        registry.index_page = 0;
+        registry.bump = ctx.bumps.registry;
+        Ok(())
+    }
+
+    // Each market setting is its own instruction argument so clients keep the flat IDL signature
+    #[allow(clippy::too_many_arguments)]
     pub fn create_market(
         ctx: Context<CreateMarket>,
-        market_id: u64,
+        creator_nonce: u64,
         question: String,
         betting_start_ts: i64,
         betting_end_ts: i64,
         resolution_end_ts: i64,
+        min_bet: u64,
+        max_bet: u64,
+        max_pool: u64,
+        exit_fee_bps: u16,
+        odds_reveal_interval: i64,
+        auditor_pubkey: Option<[u8; 32]>,
+        reveal_after_resolution: bool,
+        private_payouts: bool,
+        kind: MarketKind,
+        pricing_mode: PricingMode,
     ) -> Result<()> {
         require!(question.len() <= MAX_QUESTION_LEN, DarkPoolError::QuestionTooLong);
         require!(betting_start_ts < betting_end_ts, DarkPoolError::InvalidDeadlines);
         require!(betting_end_ts < resolution_end_ts, DarkPoolError::InvalidDeadlines);
+        require!(
+            min_bet > 0 && min_bet <= max_bet && max_bet <= max_pool,
+            DarkPoolError::InvalidBetLimits
+        );
+        require!(exit_fee_bps <= MAX_EXIT_FEE_BPS, DarkPoolError::ExitFeeTooHigh);
+        require!(odds_reveal_interval >= 0, DarkPoolError::InvalidOddsRevealInterval);
+        // Revealing a winner's stake would expose the payout that private payouts keep hidden
+        require!(!(reveal_after_resolution && private_payouts), DarkPoolError::PrivatePayoutMarket);
+        pricing_mode.validate()?;
+        require!(
+            kind == MarketKind::Confidential || pricing_mode == PricingMode::Parimutuel,
+            DarkPoolError::InvalidMarketKind
+        );
 
         let clock = Clock::get()?;
         require!(betting_start_ts >= clock.unix_timestamp, DarkPoolError::DeadlineInPast);
 
+        let index_page = ctx.accounts.registry.index_page;
+        let market_id = ctx.accounts.registry.allocate_market_id()?;
+
         let market = &mut ctx.accounts.market;
         market.authority = ctx.accounts.authority.key();
         market.market_id = market_id;
+        market.creator_nonce = creator_nonce;
+        market.index_page = index_page;
         market.question = question;
         market.token_mint = ctx.accounts.token_mint.key();
         market.vault = ctx.accounts.vault.key();
@@ -59,9 +161,44 @@ pub mod contract {
         market.betting_start_ts = betting_start_ts;
         market.betting_end_ts = betting_end_ts;
         market.resolution_end_ts = resolution_end_ts;
+        market.min_bet = min_bet;
+        market.max_bet = max_bet;
+        market.max_pool = max_pool;
+        market.total_deposited = 0;
+        market.total_paid_out = 0;
+        market.exit_fee_bps = exit_fee_bps;
+        market.fees_accrued = 0;
+        market.odds_reveal_interval = odds_reveal_interval;
+        market.last_odds_reveal_ts = 0;
+        market.pending_odds_version = 0;
+        market.pending_odds_computation = 0;
+        market.odds_snapshot_count = 0;
+        market.odds_snapshots = [OddsSnapshotEntry::default(); ODDS_SNAPSHOT_HISTORY];
+        market.auditor_pubkey = auditor_pubkey;
+        market.reveal_after_resolution = reveal_after_resolution;
+        market.private_payouts = private_payouts;
+        market.kind = kind;
+        market.revealed_yes_pool = 0;
+        market.revealed_no_pool = 0;
+        market.forfeited_pool = 0;
+        market.unrevealed_bets = 0;
+        market.pricing_mode = pricing_mode;
+        market.house_funding = 0;
+        market.house_liability = 0;
+        market.house_payouts = 0;
+        market.unclaimed_payouts = 0;
         market.status = MarketStatus::Created;
         market.winning_outcome = None;
         market.total_positions = 0;
+        market.pooled_positions = 0;
+        market.payouts_computed = 0;
+        market.private_claims = 0;
+        market.private_totals = [[0u8; 32]; 2];
+        market.private_totals_nonce = 0;
+        market.private_payout_computation = 0;
+        market.private_exposure = 0;
+        market.private_withdrawn = 0;
+        market.private_payout_total = None;
         market.state_commitment = [0u8; 32];
         market.bump = ctx.bumps.market;
         market.vault_bump = ctx.bumps.vault;
@@ -75,17 +212,33 @@ pub mod contract {
         pool_state.state_version = 0;
         pool_state.last_computation_id = 0;
         pool_state.pending_computations = 0;
+        pool_state.batch_computation_id = 0;
         pool_state.last_updated = clock.unix_timestamp;
         pool_state.bump = ctx.bumps.pool_state;
         pool_state.is_initialized = false;
 
+        let market_index = &mut ctx.accounts.market_index;
+        market_index.page = index_page;
+        market_index.bump = ctx.bumps.market_index;
+        market_index.append(market.key())?;
+
         emit!(MarketCreated {
             market: market.key(),
             market_id,
+            creator_nonce,
             authority: market.authority,
             token_mint: market.token_mint,
             betting_end_ts,
             resolution_end_ts,
+            min_bet,
+            max_bet,
+            max_pool,
+            odds_reveal_interval,
+            auditor_pubkey,
+            reveal_after_resolution,
+            private_payouts,
+            kind,
+            pricing_mode,
         });
 
         Ok(())
@@ -94,16 +247,198 @@ pub mod contract {
     pub fn open_market(ctx: Context<OpenMarket>) -> Result<()> {
         let market = &mut ctx.accounts.market;
         let pool_state = &mut ctx.accounts.pool_state;
+        let clock = Clock::get()?;
 
         require!(market.status == MarketStatus::Created, DarkPoolError::InvalidMarketStatus);
+        require!(market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
 
         pool_state.is_initialized = true;
         pool_state.state_version = 1;
+        pool_state.last_updated = clock.unix_timestamp;
         market.status = MarketStatus::Open;
+        ctx.accounts.market_index.set_status(market.key(), market.status)?;
 
         emit!(MarketOpened {
             market: market.key(),
-            opened_at: Clock::get()?.unix_timestamp,
+            seed_position: Pubkey::default(),
+            seed_amount: 0,
+            opened_at: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn open_seeded_market(
+        ctx: Context<OpenSeededMarket>,
+        computation_offset: u64,
+        encrypted_seed: Vec<u8>,
+        creator_pubkey: [u8; 32],
+        nonce: u128,
+        seed_amount: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        require!(ctx.accounts.market.status == MarketStatus::Created, DarkPoolError::InvalidMarketStatus);
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
+        require!(encrypted_seed.len() == 64, DarkPoolError::InvalidEncryptedBetSize);
+        // Markets without liquidity open synchronously through open_market
+        require!(seed_amount > 0, DarkPoolError::InvalidBetAmount);
+        require!(seed_amount <= ctx.accounts.market.max_pool, DarkPoolError::PoolCapExceeded);
+
+        ctx.accounts.market.reserve_liability(seed_amount)?;
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.authority_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        );
+        token::transfer(transfer_ctx, seed_amount)?;
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let encrypted_yes: [u8; 32] = encrypted_seed[0..32].try_into().unwrap();
+        let encrypted_no: [u8; 32] = encrypted_seed[32..64].try_into().unwrap();
+
+        let args = ArgBuilder::new()
+            .x25519_pubkey(creator_pubkey)
+            .plaintext_u128(nonce)
+            .encrypted_u64(encrypted_yes)
+            .encrypted_u64(encrypted_no)
+            .plaintext_u64(seed_amount)
+            .build();
+
+        let market_key = ctx.accounts.market.key();
+        let seed_position_key = ctx.accounts.seed_position.key();
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![SeedLiquidityCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[
+                    CallbackAccount { pubkey: market_key, is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.pool_state.key(), is_writable: true },
+                    CallbackAccount { pubkey: seed_position_key, is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.creator_portfolio.key(), is_writable: true },
+                ],
+            )?],
+            1,
+            0,
+        )?;
+
+        let asset_seeds: &[&[u8]] = &[POSITION_ASSET_SEED, seed_position_key.as_ref(), &[ctx.bumps.position_asset]];
+        mint_position_asset(
+            &ctx.accounts.mpl_core_program.to_account_info(),
+            &ctx.accounts.position_asset.to_account_info(),
+            &ctx.accounts.market.to_account_info(),
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.market.market_id,
+            seed_amount,
+            asset_seeds,
+        )?;
+
+        let position = &mut ctx.accounts.seed_position;
+        position.market = market_key;
+        position.owner = ctx.accounts.authority.key();
+        position.encrypted_bet = encrypted_seed;
+        position.user_pubkey = creator_pubkey;
+        position.nonce = nonce;
+        position.bet_commitment = None;
+        position.encrypted_stake = [[0u8; 32]; 2];
+        position.stake_nonce = 0;
+        position.kind = PositionKind::CreatorSeed;
+        position.asset = ctx.accounts.position_asset.key();
+        position.payout_destination = None;
+        position.delegate = None;
+        position.deposit_amount = seed_amount;
+        (position.locked_yes_odds_bps, position.locked_no_odds_bps) = ctx.accounts.market.pricing_mode.odds();
+        position.payout_amount = 0;
+        position.status = PositionStatus::Pending;
+        position.computation_id = computation_offset;
+        position.pool_version = 0;
+        position.disclosure_computation_id = 0;
+        position.audited = false;
+        position.revealed_outcome = None;
+        position.revealed_amount = None;
+        position.encrypted_payout = None;
+        position.payout_nonce = 0;
+        position.bump = ctx.bumps.seed_position;
+        position.created_at = clock.unix_timestamp;
+        position.processed_at = None;
+        position.claimed_at = None;
+
+        let creator_portfolio = &mut ctx.accounts.creator_portfolio;
+        creator_portfolio.owner = ctx.accounts.authority.key();
+        creator_portfolio.bump = ctx.bumps.creator_portfolio;
+        creator_portfolio.track(market_key, seed_position_key, PositionStatus::Pending)?;
+
+        let market = &mut ctx.accounts.market;
+        market.total_positions = market.total_positions.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+        market.total_deposited = market.total_deposited.checked_add(seed_amount).ok_or(DarkPoolError::Overflow)?;
+        market.status = MarketStatus::Open;
+        ctx.accounts.market_index.set_status(market_key, market.status)?;
+
+        ctx.accounts.pool_state.last_computation_id = computation_offset;
+        ctx.accounts.pool_state.pending_computations = ctx.accounts.pool_state.pending_computations.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+
+        emit!(MarketOpened {
+            market: market_key,
+            seed_position: seed_position_key,
+            seed_amount,
+            opened_at: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "seed_liquidity")]
+    pub fn seed_liquidity_callback(
+        ctx: Context<SeedLiquidityCallback>,
+        output: SignedComputationOutputs<SeedLiquidityOutput>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let result = output
+            .verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account)
+            .map_err(|_| DarkPoolError::ComputationAborted)?;
+        let pool = result.field_0.field_0;
+        let stake = result.field_0.field_1;
+
+        let pool_state = &mut ctx.accounts.pool_state;
+        pool_state.set_encrypted_state(pool.nonce, &pool.ciphertexts);
+        pool_state.is_initialized = true;
+        pool_state.state_version = 1;
+        pool_state.last_updated = clock.unix_timestamp;
+        pool_state.pending_computations = pool_state.pending_computations.saturating_sub(1);
+
+        let previous_commitment = ctx.accounts.market.state_commitment;
+        let commitment = pool_state.commitment(&previous_commitment);
+        ctx.accounts.market.state_commitment = commitment;
+
+        let position = &mut ctx.accounts.seed_position;
+        position.encrypted_stake = stake.ciphertexts;
+        position.stake_nonce = stake.nonce;
+        position.status = PositionStatus::Processed;
+        position.processed_at = Some(clock.unix_timestamp);
+        UserPortfolio::update_status(&ctx.accounts.creator_portfolio, position.owner, position.key(), PositionStatus::Processed)?;
+        ctx.accounts.market.pooled_positions = ctx.accounts.market.pooled_positions.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+
+        emit!(PoolStateUpdated {
+            market: ctx.accounts.market.key(),
+            state_version: pool_state.state_version,
+            nonce: pool_state.nonce,
+            encrypted_state: pool_state.encrypted_state.clone(),
+            previous_commitment,
+            commitment,
+            updated_at: clock.unix_timestamp,
         });
 
         Ok(())
@@ -116,14 +451,26 @@ pub mod contract {
         user_pubkey: [u8; 32],
         nonce: u128,
         deposit_amount: u64,
+        payout_destination: Option<Pubkey>,
     ) -> Result<()> {
         let clock = Clock::get()?;
 
         require!(ctx.accounts.market.status == MarketStatus::Open, DarkPoolError::MarketNotOpen);
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
         require!(clock.unix_timestamp >= ctx.accounts.market.betting_start_ts, DarkPoolError::BettingNotStarted);
         require!(clock.unix_timestamp < ctx.accounts.market.betting_end_ts, DarkPoolError::BettingEnded);
         require!(deposit_amount > 0, DarkPoolError::InvalidBetAmount);
+        require!(deposit_amount >= ctx.accounts.market.min_bet, DarkPoolError::BetBelowMinimum);
+        require!(deposit_amount <= ctx.accounts.market.max_bet, DarkPoolError::BetAboveMaximum);
         require!(encrypted_bet.len() == 64, DarkPoolError::InvalidEncryptedBetSize);
+        require!(
+            ctx.accounts.audit_record.is_some() == ctx.accounts.market.auditor_pubkey.is_some(),
+            DarkPoolError::AuditRecordMismatch
+        );
+
+        let total_deposited = ctx.accounts.market.total_deposited.checked_add(deposit_amount).ok_or(DarkPoolError::Overflow)?;
+        require!(total_deposited <= ctx.accounts.market.max_pool, DarkPoolError::PoolCapExceeded);
+        ctx.accounts.market.reserve_liability(deposit_amount)?;
 
         let transfer_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
@@ -145,8 +492,28 @@ pub mod contract {
             .plaintext_u128(nonce)
             .encrypted_u8(encrypted_outcome)
             .encrypted_u64(encrypted_amount)
+            .plaintext_u128(ctx.accounts.pool_state.nonce)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext_or_empty(0)?)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext_or_empty(1)?)
+            .plaintext_u8(ctx.accounts.pool_state.is_empty() as u8)
+            .plaintext_u64(deposit_amount)
+            .plaintext_u64(ctx.accounts.market.min_bet)
+            .plaintext_u64(ctx.accounts.market.max_bet)
+            // Without an auditor the copy is encrypted back to the bettor and discarded
+            .x25519_pubkey(ctx.accounts.market.auditor_pubkey.unwrap_or(user_pubkey))
+            .plaintext_u128(nonce)
             .build();
 
+        let mut callback_accounts = vec![
+            CallbackAccount { pubkey: ctx.accounts.market.key(), is_writable: true },
+            CallbackAccount { pubkey: ctx.accounts.pool_state.key(), is_writable: true },
+            CallbackAccount { pubkey: ctx.accounts.user_position.key(), is_writable: true },
+            CallbackAccount { pubkey: ctx.accounts.user_portfolio.key(), is_writable: true },
+        ];
+        if let Some(audit_record) = &ctx.accounts.audit_record {
+            callback_accounts.push(CallbackAccount { pubkey: audit_record.key(), is_writable: true });
+        }
+
         queue_computation(
             ctx.accounts,
             computation_offset,
@@ -155,12 +522,26 @@ pub mod contract {
             vec![ProcessBetCallback::callback_ix(
                 computation_offset,
                 &ctx.accounts.mxe_account,
-                &[],
+                &callback_accounts,
             )?],
             1,
             0,
         )?;
 
+        let position_key = ctx.accounts.user_position.key();
+        let asset_seeds: &[&[u8]] = &[POSITION_ASSET_SEED, position_key.as_ref(), &[ctx.bumps.position_asset]];
+        mint_position_asset(
+            &ctx.accounts.mpl_core_program.to_account_info(),
+            &ctx.accounts.position_asset.to_account_info(),
+            &ctx.accounts.market.to_account_info(),
+            &ctx.accounts.bettor.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.market.market_id,
+            deposit_amount,
+            asset_seeds,
+        )?;
+
         let position = &mut ctx.accounts.user_position;
         let market_key = ctx.accounts.market.key();
         position.market = market_key;
@@ -168,16 +549,49 @@ pub mod contract {
         position.encrypted_bet = encrypted_bet;
         position.user_pubkey = user_pubkey;
         position.nonce = nonce;
+        position.bet_commitment = None;
+        position.encrypted_stake = [[0u8; 32]; 2];
+        position.stake_nonce = 0;
+        position.kind = PositionKind::Bettor;
+        position.asset = ctx.accounts.position_asset.key();
+        position.payout_destination = payout_destination;
+        position.delegate = None;
         position.deposit_amount = deposit_amount;
+        (position.locked_yes_odds_bps, position.locked_no_odds_bps) = ctx.accounts.market.pricing_mode.odds();
         position.payout_amount = 0;
         position.status = PositionStatus::Pending;
         position.computation_id = computation_offset;
+        position.pool_version = ctx.accounts.pool_state.state_version;
+        position.disclosure_computation_id = 0;
+        position.audited = false;
+        position.revealed_outcome = None;
+        position.revealed_amount = None;
+        position.encrypted_payout = None;
+        position.payout_nonce = 0;
         position.bump = ctx.bumps.user_position;
         position.created_at = clock.unix_timestamp;
         position.processed_at = None;
         position.claimed_at = None;
 
+        let portfolio = &mut ctx.accounts.user_portfolio;
+        portfolio.owner = ctx.accounts.bettor.key();
+        portfolio.bump = ctx.bumps.user_portfolio;
+        portfolio.track(market_key, position.key(), PositionStatus::Pending)?;
+
+        if let (Some(audit_record), Some(auditor_pubkey)) =
+            (ctx.accounts.audit_record.as_mut(), ctx.accounts.market.auditor_pubkey)
+        {
+            audit_record.market = market_key;
+            audit_record.position = position.key();
+            audit_record.auditor_pubkey = auditor_pubkey;
+            audit_record.nonce = 0;
+            audit_record.encrypted_stake = [[0u8; 32]; 2];
+            audit_record.recorded_at = None;
+            audit_record.bump = ctx.bumps.audit_record.unwrap_or_default();
+        }
+
         ctx.accounts.market.total_positions = ctx.accounts.market.total_positions.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+        ctx.accounts.market.total_deposited = total_deposited;
         ctx.accounts.pool_state.pending_computations = ctx.accounts.pool_state.pending_computations.checked_add(1).ok_or(DarkPoolError::Overflow)?;
 
         emit!(BetPlaced {
@@ -185,6 +599,7 @@ pub mod contract {
             position: position.key(),
             bettor: ctx.accounts.bettor.key(),
             deposit_amount,
+            state_version: ctx.accounts.pool_state.state_version,
             computation_id: computation_offset,
         });
 
@@ -202,25 +617,70 @@ pub mod contract {
             .verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account)
             .map_err(|_| DarkPoolError::ComputationAborted)?;
 
-        ctx.accounts.pool_state.encrypted_state = result
-            .field_0
-            .ciphertexts
-            .iter()
-            .flat_map(|chunk| chunk.iter().copied())
-            .collect();
+        // Another update landed since this bet read the pool; applying it would drop that update
+        if ctx.accounts.pool_state.state_version != ctx.accounts.user_position.pool_version {
+            ctx.accounts.pool_state.pending_computations = ctx.accounts.pool_state.pending_computations.saturating_sub(1);
+
+            emit!(PoolStateConflict {
+                market: ctx.accounts.market.key(),
+                position: ctx.accounts.user_position.key(),
+                read_version: ctx.accounts.user_position.pool_version,
+                current_version: ctx.accounts.pool_state.state_version,
+            });
+
+            return Ok(());
+        }
+
+        let pool = result.field_0.field_0;
+        let stake = result.field_0.field_1;
+
+        ctx.accounts.pool_state.set_encrypted_state(pool.nonce, &pool.ciphertexts);
         ctx.accounts.pool_state.state_version = ctx.accounts.pool_state.state_version.checked_add(1).ok_or(DarkPoolError::Overflow)?;
         ctx.accounts.pool_state.last_updated = clock.unix_timestamp;
         ctx.accounts.pool_state.last_computation_id = ctx.accounts.user_position.computation_id;
 
-        let mut commitment = [0u8; 32];
-        commitment[..16].copy_from_slice(&result.field_0.nonce.to_le_bytes());
+        let previous_commitment = ctx.accounts.market.state_commitment;
+        let commitment = ctx.accounts.pool_state.commitment(&previous_commitment);
         ctx.accounts.market.state_commitment = commitment;
 
+        ctx.accounts.user_position.encrypted_stake = stake.ciphertexts;
+        ctx.accounts.user_position.stake_nonce = stake.nonce;
         ctx.accounts.user_position.status = PositionStatus::Processed;
         ctx.accounts.user_position.processed_at = Some(clock.unix_timestamp);
+        UserPortfolio::update_status(
+            &ctx.accounts.user_portfolio,
+            ctx.accounts.user_position.owner,
+            ctx.accounts.user_position.key(),
+            PositionStatus::Processed,
+        )?;
+        ctx.accounts.market.pooled_positions = ctx.accounts.market.pooled_positions.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+
+        if ctx.accounts.market.auditor_pubkey.is_some() {
+            let info = ctx.remaining_accounts.first().ok_or(DarkPoolError::AuditRecordMismatch)?;
+            require_keys_eq!(*info.owner, crate::ID, DarkPoolError::AuditRecordMismatch);
+            let mut data = info.try_borrow_mut_data()?;
+            let mut audit_record = AuditRecord::try_deserialize(&mut &data[..])?;
+            require_keys_eq!(audit_record.position, ctx.accounts.user_position.key(), DarkPoolError::AuditRecordMismatch);
+
+            let audit_stake = result.field_0.field_2;
+            audit_record.nonce = audit_stake.nonce;
+            audit_record.encrypted_stake = audit_stake.ciphertexts;
+            audit_record.recorded_at = Some(clock.unix_timestamp);
+            audit_record.try_serialize(&mut &mut data[..])?;
+        }
 
         ctx.accounts.pool_state.pending_computations = ctx.accounts.pool_state.pending_computations.saturating_sub(1);
 
+        emit!(PoolStateUpdated {
+            market: ctx.accounts.market.key(),
+            state_version: ctx.accounts.pool_state.state_version,
+            nonce: ctx.accounts.pool_state.nonce,
+            encrypted_state: ctx.accounts.pool_state.encrypted_state.clone(),
+            previous_commitment,
+            commitment,
+            updated_at: clock.unix_timestamp,
+        });
+
         emit!(BetProcessed {
             market: ctx.accounts.market.key(),
             position: ctx.accounts.user_position.key(),
@@ -231,328 +691,4617 @@ pub mod contract {
         Ok(())
     }
 
-    pub fn close_betting(ctx: Context<CloseBetting>) -> Result<()> {
-        let market = &mut ctx.accounts.market;
-        let clock = Clock::get()?;
-
-        require!(market.status == MarketStatus::Open, DarkPoolError::InvalidMarketStatus);
-        require!(clock.unix_timestamp >= market.betting_end_ts, DarkPoolError::BettingNotEnded);
-
-        market.status = MarketStatus::BettingClosed;
-
-        emit!(BettingClosed {
-            market: market.key(),
-            total_positions: market.total_positions,
-            closed_at: clock.unix_timestamp,
-        });
-
-        Ok(())
-    }
-
-    pub fn resolve_market(ctx: Context<ResolveMarket>, winning_outcome: u8) -> Result<()> {
-        let market = &mut ctx.accounts.market;
-        let clock = Clock::get()?;
-
+    pub fn requeue_bet(ctx: Context<RequeueBet>, computation_offset: u64) -> Result<()> {
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
         require!(
-            market.status == MarketStatus::Open || market.status == MarketStatus::BettingClosed,
+            ctx.accounts.market.status == MarketStatus::Open || ctx.accounts.market.status == MarketStatus::BettingClosed,
             DarkPoolError::InvalidMarketStatus
         );
-        require!(clock.unix_timestamp >= market.betting_end_ts, DarkPoolError::BettingNotEnded);
-        require!(winning_outcome <= 1, DarkPoolError::InvalidOutcome);
-
-        market.winning_outcome = Some(winning_outcome);
-        market.status = MarketStatus::Resolved;
-        market.resolved_at = Some(clock.unix_timestamp);
-
-        emit!(MarketResolved {
-            market: market.key(),
-            winning_outcome,
-            resolved_at: clock.unix_timestamp,
-        });
-
-        Ok(())
-    }
-
-    pub fn compute_payout(ctx: Context<ComputePayout>, computation_offset: u64) -> Result<()> {
-        let winning_outcome = ctx.accounts.market.winning_outcome.ok_or(DarkPoolError::MarketNotResolved)?;
+        // Queued bets have never been sent to MPC; process_bet_batch picks them up
+        require!(ctx.accounts.user_position.computation_id != 0, DarkPoolError::BetStillQueued);
+        require!(
+            ctx.accounts.user_position.computation_id != ctx.accounts.pool_state.batch_computation_id,
+            DarkPoolError::ComputationInFlight
+        );
+        require!(
+            ctx.accounts.user_position.pool_version != ctx.accounts.pool_state.state_version,
+            DarkPoolError::BetNotStale
+        );
 
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
 
+        let position = &ctx.accounts.user_position;
+        let encrypted_outcome: [u8; 32] = position.encrypted_bet[0..32].try_into().unwrap();
+        let encrypted_amount: [u8; 32] = position.encrypted_bet[32..64].try_into().unwrap();
+
         let args = ArgBuilder::new()
-            .x25519_pubkey(ctx.accounts.user_position.user_pubkey)
-            .plaintext_u128(ctx.accounts.user_position.nonce)
-            .plaintext_u8(winning_outcome)
-            .plaintext_u64(ctx.accounts.user_position.deposit_amount)
+            .x25519_pubkey(position.user_pubkey)
+            .plaintext_u128(position.nonce)
+            .encrypted_u8(encrypted_outcome)
+            .encrypted_u64(encrypted_amount)
+            .plaintext_u128(ctx.accounts.pool_state.nonce)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext_or_empty(0)?)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext_or_empty(1)?)
+            .plaintext_u8(ctx.accounts.pool_state.is_empty() as u8)
+            .plaintext_u64(position.deposit_amount)
+            .plaintext_u64(ctx.accounts.market.min_bet)
+            .plaintext_u64(ctx.accounts.market.max_bet)
+            .x25519_pubkey(ctx.accounts.market.auditor_pubkey.unwrap_or(position.user_pubkey))
+            .plaintext_u128(position.nonce)
             .build();
 
+        let mut callback_accounts = vec![
+            CallbackAccount { pubkey: ctx.accounts.market.key(), is_writable: true },
+            CallbackAccount { pubkey: ctx.accounts.pool_state.key(), is_writable: true },
+            CallbackAccount { pubkey: ctx.accounts.user_position.key(), is_writable: true },
+            CallbackAccount { pubkey: UserPortfolio::address_for(&position.owner), is_writable: true },
+        ];
+        if ctx.accounts.market.auditor_pubkey.is_some() {
+            let (audit_record, _) = Pubkey::find_program_address(
+                &[AUDIT_RECORD_SEED, ctx.accounts.user_position.key().as_ref()],
+                &crate::ID,
+            );
+            callback_accounts.push(CallbackAccount { pubkey: audit_record, is_writable: true });
+        }
+
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![ComputePayoutCallback::callback_ix(
+            vec![ProcessBetCallback::callback_ix(
                 computation_offset,
                 &ctx.accounts.mxe_account,
-                &[],
+                &callback_accounts,
             )?],
             1,
             0,
         )?;
 
-        Ok(())
-    }
-
-    #[arcium_callback(encrypted_ix = "compute_payout")]
-    pub fn compute_payout_callback(
-        ctx: Context<ComputePayoutCallback>,
-        output: SignedComputationOutputs<ComputePayoutOutput>,
-    ) -> Result<()> {
-        let result = output
-            .verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account)
-            .map_err(|_| DarkPoolError::ComputationAborted)?;
-
-        let payout_bytes = &result.field_0.ciphertexts[0];
-        let payout_amount = u64::from_le_bytes(payout_bytes[..8].try_into().unwrap_or([0u8; 8]));
+        let position = &mut ctx.accounts.user_position;
+        position.computation_id = computation_offset;
+        position.pool_version = ctx.accounts.pool_state.state_version;
 
-        ctx.accounts.user_position.payout_amount = payout_amount;
-        ctx.accounts.user_position.status = PositionStatus::PayoutComputed;
+        ctx.accounts.pool_state.pending_computations = ctx.accounts.pool_state.pending_computations.checked_add(1).ok_or(DarkPoolError::Overflow)?;
 
-        emit!(PayoutComputed {
+        emit!(BetPlaced {
             market: ctx.accounts.market.key(),
-            position: ctx.accounts.user_position.key(),
-            user: ctx.accounts.user_position.owner,
-            payout_amount,
+            position: position.key(),
+            bettor: position.owner,
+            deposit_amount: position.deposit_amount,
+            state_version: position.pool_version,
+            computation_id: computation_offset,
         });
 
         Ok(())
     }
 
-    pub fn claim_payout(ctx: Context<ClaimPayout>) -> Result<()> {
-        let market = &ctx.accounts.market;
-        let position = &mut ctx.accounts.user_position;
+    pub fn queue_bet(
+        ctx: Context<QueueBet>,
+        encrypted_bet: Vec<u8>,
+        user_pubkey: [u8; 32],
+        nonce: u128,
+        deposit_amount: u64,
+        payout_destination: Option<Pubkey>,
+    ) -> Result<()> {
         let clock = Clock::get()?;
 
-        require!(position.payout_amount > 0, DarkPoolError::NoPayout);
+        require!(ctx.accounts.market.status == MarketStatus::Open, DarkPoolError::MarketNotOpen);
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
+        require!(clock.unix_timestamp >= ctx.accounts.market.betting_start_ts, DarkPoolError::BettingNotStarted);
+        require!(clock.unix_timestamp < ctx.accounts.market.betting_end_ts, DarkPoolError::BettingEnded);
+        require!(deposit_amount > 0, DarkPoolError::InvalidBetAmount);
+        require!(deposit_amount >= ctx.accounts.market.min_bet, DarkPoolError::BetBelowMinimum);
+        require!(deposit_amount <= ctx.accounts.market.max_bet, DarkPoolError::BetAboveMaximum);
+        require!(encrypted_bet.len() == 64, DarkPoolError::InvalidEncryptedBetSize);
+        require!(ctx.accounts.market.auditor_pubkey.is_none(), DarkPoolError::AuditedMarketRequiresPlaceBet);
 
-        let payout_amount = position.payout_amount;
+        let total_deposited = ctx.accounts.market.total_deposited.checked_add(deposit_amount).ok_or(DarkPoolError::Overflow)?;
+        require!(total_deposited <= ctx.accounts.market.max_pool, DarkPoolError::PoolCapExceeded);
+        ctx.accounts.market.reserve_liability(deposit_amount)?;
 
-        let market_id_bytes = market.market_id.to_le_bytes();
-        let seeds = &[VAULT_SEED, market_id_bytes.as_ref(), &[market.vault_bump]];
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.bettor_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.bettor.to_account_info(),
+            },
+        );
+        token::transfer(transfer_ctx, deposit_amount)?;
+
+        let position_key = ctx.accounts.user_position.key();
+        let asset_seeds: &[&[u8]] = &[POSITION_ASSET_SEED, position_key.as_ref(), &[ctx.bumps.position_asset]];
+        mint_position_asset(
+            &ctx.accounts.mpl_core_program.to_account_info(),
+            &ctx.accounts.position_asset.to_account_info(),
+            &ctx.accounts.market.to_account_info(),
+            &ctx.accounts.bettor.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.market.market_id,
+            deposit_amount,
+            asset_seeds,
+        )?;
+
+        let position = &mut ctx.accounts.user_position;
+        let market_key = ctx.accounts.market.key();
+        position.market = market_key;
+        position.owner = ctx.accounts.bettor.key();
+        position.encrypted_bet = encrypted_bet;
+        position.user_pubkey = user_pubkey;
+        position.nonce = nonce;
+        position.bet_commitment = None;
+        position.encrypted_stake = [[0u8; 32]; 2];
+        position.stake_nonce = 0;
+        position.kind = PositionKind::Bettor;
+        position.asset = ctx.accounts.position_asset.key();
+        position.payout_destination = payout_destination;
+        position.delegate = None;
+        position.deposit_amount = deposit_amount;
+        (position.locked_yes_odds_bps, position.locked_no_odds_bps) = ctx.accounts.market.pricing_mode.odds();
+        position.payout_amount = 0;
+        position.status = PositionStatus::Pending;
+        position.computation_id = 0;
+        position.pool_version = 0;
+        position.disclosure_computation_id = 0;
+        position.audited = false;
+        position.revealed_outcome = None;
+        position.revealed_amount = None;
+        position.encrypted_payout = None;
+        position.payout_nonce = 0;
+        position.bump = ctx.bumps.user_position;
+        position.created_at = clock.unix_timestamp;
+        position.processed_at = None;
+        position.claimed_at = None;
+
+        let portfolio = &mut ctx.accounts.user_portfolio;
+        portfolio.owner = ctx.accounts.bettor.key();
+        portfolio.bump = ctx.bumps.user_portfolio;
+        portfolio.track(market_key, position_key, PositionStatus::Pending)?;
+
+        let pending_bets = &mut ctx.accounts.pending_bets;
+        pending_bets.market = market_key;
+        pending_bets.bump = ctx.bumps.pending_bets;
+        pending_bets.push(position_key)?;
+
+        ctx.accounts.market.total_positions = ctx.accounts.market.total_positions.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+        ctx.accounts.market.total_deposited = total_deposited;
+
+        emit!(BetQueued {
+            market: market_key,
+            position: position_key,
+            bettor: ctx.accounts.bettor.key(),
+            deposit_amount,
+            queue_length: pending_bets.positions.len() as u32,
+        });
+
+        Ok(())
+    }
+
+    pub fn process_bet_batch(ctx: Context<ProcessBetBatch>, computation_offset: u64) -> Result<()> {
+        require!(
+            ctx.accounts.market.status == MarketStatus::Open || ctx.accounts.market.status == MarketStatus::BettingClosed,
+            DarkPoolError::InvalidMarketStatus
+        );
+        require!(ctx.accounts.pool_state.batch_computation_id == 0, DarkPoolError::BatchInFlight);
+
+        let batch = ctx.accounts.pending_bets.take_batch(BET_BATCH_SIZE);
+        require!(!batch.is_empty(), DarkPoolError::PendingQueueEmpty);
+        require!(ctx.remaining_accounts.len() == batch.len(), DarkPoolError::PendingBetMismatch);
+
+        let mut keys = Vec::with_capacity(batch.len());
+        let mut positions = Vec::with_capacity(batch.len());
+        for (info, expected) in ctx.remaining_accounts.iter().zip(batch.iter()) {
+            require_keys_eq!(info.key(), *expected, DarkPoolError::PendingBetMismatch);
+            require_keys_eq!(*info.owner, crate::ID, DarkPoolError::InvalidPosition);
+            let mut data = info.try_borrow_mut_data()?;
+            let mut position = UserPosition::try_deserialize(&mut &data[..])?;
+            // Entries settled some other way are dropped so they can't block the queue
+            if position.status != PositionStatus::Pending {
+                continue;
+            }
+
+            position.computation_id = computation_offset;
+            position.pool_version = ctx.accounts.pool_state.state_version;
+            position.try_serialize(&mut &mut data[..])?;
+            keys.push(info.key());
+            positions.push(position);
+        }
+        if positions.is_empty() {
+            return Ok(());
+        }
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        // Unused slots repeat the first bet; the circuit masks them out by count
+        let mut args = ArgBuilder::new();
+        for slot in 0..BET_BATCH_SIZE {
+            let position = positions.get(slot).unwrap_or(&positions[0]);
+            let encrypted_outcome: [u8; 32] = position.encrypted_bet[0..32].try_into().unwrap();
+            let encrypted_amount: [u8; 32] = position.encrypted_bet[32..64].try_into().unwrap();
+            args = args
+                .x25519_pubkey(position.user_pubkey)
+                .plaintext_u128(position.nonce)
+                .encrypted_u8(encrypted_outcome)
+                .encrypted_u64(encrypted_amount);
+        }
+        args = args
+            .plaintext_u128(ctx.accounts.pool_state.nonce)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext_or_empty(0)?)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext_or_empty(1)?)
+            .plaintext_u8(ctx.accounts.pool_state.is_empty() as u8);
+        for slot in 0..BET_BATCH_SIZE {
+            args = args.plaintext_u64(positions.get(slot).unwrap_or(&positions[0]).deposit_amount);
+        }
+        let args = args
+            .plaintext_u64(ctx.accounts.market.min_bet)
+            .plaintext_u64(ctx.accounts.market.max_bet)
+            .plaintext_u8(positions.len() as u8)
+            .build();
+
+        let mut callback_accounts = vec![
+            CallbackAccount { pubkey: ctx.accounts.market.key(), is_writable: true },
+            CallbackAccount { pubkey: ctx.accounts.pool_state.key(), is_writable: true },
+        ];
+        callback_accounts.extend(keys.iter().map(|position| CallbackAccount { pubkey: *position, is_writable: true }));
+        callback_accounts.extend(positions.iter().map(|position| CallbackAccount {
+            pubkey: UserPortfolio::address_for(&position.owner),
+            is_writable: true,
+        }));
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ProcessBetBatchCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &callback_accounts,
+            )?],
+            1,
+            0,
+        )?;
+
+        ctx.accounts.pool_state.last_computation_id = computation_offset;
+        ctx.accounts.pool_state.batch_computation_id = computation_offset;
+        ctx.accounts.pool_state.pending_computations = ctx.accounts.pool_state.pending_computations.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+
+        emit!(BetBatchQueued {
+            market: ctx.accounts.market.key(),
+            positions: keys,
+            computation_id: computation_offset,
+        });
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "process_bet_batch")]
+    pub fn process_bet_batch_callback(
+        ctx: Context<ProcessBetBatchCallback>,
+        output: SignedComputationOutputs<ProcessBetBatchOutput>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let market_key = ctx.accounts.market.key();
+        let current_version = ctx.accounts.pool_state.state_version;
+        let batch_id = ctx.accounts.pool_state.batch_computation_id;
+        ctx.accounts.pool_state.batch_computation_id = 0;
+        ctx.accounts.pool_state.pending_computations = ctx.accounts.pool_state.pending_computations.saturating_sub(1);
+
+        let (position_infos, portfolio_infos) = ctx.remaining_accounts.split_at(ctx.remaining_accounts.len() / 2);
+        let mut positions = Vec::with_capacity(position_infos.len());
+        for info in position_infos {
+            require_keys_eq!(*info.owner, crate::ID, DarkPoolError::InvalidPosition);
+            let position = UserPosition::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            require_keys_eq!(position.market, market_key, DarkPoolError::InvalidPosition);
+            require!(position.computation_id == batch_id, DarkPoolError::ComputationMismatch);
+            require!(position.status == PositionStatus::Pending, DarkPoolError::PendingBetMismatch);
+            positions.push(position);
+        }
+
+        let result = match output.verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account) {
+            Ok(result) => result,
+            Err(_) => {
+                // Zero read versions are always stale, so requeue_bet can send each bet on its own
+                for (info, position) in position_infos.iter().zip(positions.iter_mut()) {
+                    position.pool_version = 0;
+                    position.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+                }
+
+                emit!(BetBatchAborted {
+                    market: market_key,
+                    positions: position_infos.iter().map(|info| info.key()).collect(),
+                    computation_id: batch_id,
+                });
+
+                return Ok(());
+            }
+        };
+
+        // Stale bets stay Pending for requeue_bet
+        if positions.iter().any(|position| position.pool_version != current_version) {
+            for (info, position) in position_infos.iter().zip(positions.iter()) {
+                emit!(PoolStateConflict {
+                    market: market_key,
+                    position: info.key(),
+                    read_version: position.pool_version,
+                    current_version,
+                });
+            }
+            return Ok(());
+        }
+
+        let pool = result.field_0.field_0;
+        let stakes = [
+            result.field_0.field_1,
+            result.field_0.field_2,
+            result.field_0.field_3,
+            result.field_0.field_4,
+        ];
+
+        ctx.accounts.pool_state.set_encrypted_state(pool.nonce, &pool.ciphertexts);
+        ctx.accounts.pool_state.state_version = ctx.accounts.pool_state.state_version.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+        ctx.accounts.pool_state.last_updated = clock.unix_timestamp;
+
+        let previous_commitment = ctx.accounts.market.state_commitment;
+        let commitment = ctx.accounts.pool_state.commitment(&previous_commitment);
+        ctx.accounts.market.state_commitment = commitment;
+
+        emit!(PoolStateUpdated {
+            market: market_key,
+            state_version: ctx.accounts.pool_state.state_version,
+            nonce: ctx.accounts.pool_state.nonce,
+            encrypted_state: ctx.accounts.pool_state.encrypted_state.clone(),
+            previous_commitment,
+            commitment,
+            updated_at: clock.unix_timestamp,
+        });
+
+        // Each position's portfolio follows the batch of positions
+        let slots = position_infos.iter().zip(portfolio_infos).zip(positions.iter_mut()).zip(stakes.iter());
+        for (((info, portfolio_info), position), stake) in slots {
+            position.encrypted_stake = stake.ciphertexts;
+            position.stake_nonce = stake.nonce;
+            position.status = PositionStatus::Processed;
+            position.processed_at = Some(clock.unix_timestamp);
+            position.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+            UserPortfolio::update_status(portfolio_info, position.owner, info.key(), PositionStatus::Processed)?;
+            ctx.accounts.market.pooled_positions = ctx.accounts.market.pooled_positions.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+
+            emit!(BetProcessed {
+                market: market_key,
+                position: info.key(),
+                state_version: ctx.accounts.pool_state.state_version,
+                new_commitment: commitment,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn withdraw_bet(ctx: Context<WithdrawBet>, computation_offset: u64) -> Result<()> {
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
+        let clock = Clock::get()?;
+
+        require!(ctx.accounts.market.status == MarketStatus::Open, DarkPoolError::MarketNotOpen);
+        require!(clock.unix_timestamp < ctx.accounts.market.betting_end_ts, DarkPoolError::BettingEnded);
+        require!(ctx.accounts.user_position.kind == PositionKind::Bettor, DarkPoolError::SeedPositionLocked);
+        require!(
+            ctx.accounts.user_position.holder(&ctx.accounts.position_asset)? == ctx.accounts.bettor.key(),
+            DarkPoolError::Unauthorized
+        );
+
+        // The asset may have changed hands outside the program since the bet was placed
+        let bettor = ctx.accounts.bettor.key();
+        let portfolio = &mut ctx.accounts.user_portfolio;
+        portfolio.owner = bettor;
+        portfolio.bump = ctx.bumps.user_portfolio;
+        portfolio.track(ctx.accounts.market.key(), ctx.accounts.user_position.key(), PositionStatus::Withdrawing)?;
+        ctx.accounts.user_position.set_owner(bettor);
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let args = ArgBuilder::new()
+            .plaintext_u128(ctx.accounts.user_position.stake_nonce)
+            .encrypted_u64(ctx.accounts.user_position.encrypted_stake[0])
+            .encrypted_u64(ctx.accounts.user_position.encrypted_stake[1])
+            .plaintext_u128(ctx.accounts.pool_state.nonce)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext(0)?)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext(1)?)
+            .build();
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![WithdrawBetCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[
+                    CallbackAccount { pubkey: ctx.accounts.market.key(), is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.pool_state.key(), is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.user_position.key(), is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.user_portfolio.key(), is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.bettor_token_account.key(), is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.vault.key(), is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.token_program.key(), is_writable: false },
+                ],
+            )?],
+            1,
+            0,
+        )?;
+
+        let position = &mut ctx.accounts.user_position;
+        position.status = PositionStatus::Withdrawing;
+        position.computation_id = computation_offset;
+        position.pool_version = ctx.accounts.pool_state.state_version;
+
+        ctx.accounts.pool_state.pending_computations = ctx.accounts.pool_state.pending_computations.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+
+        emit!(BetWithdrawalRequested {
+            market: ctx.accounts.market.key(),
+            position: position.key(),
+            bettor: position.owner,
+            computation_id: computation_offset,
+        });
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "withdraw_bet")]
+    pub fn withdraw_bet_callback(
+        ctx: Context<WithdrawBetCallback>,
+        output: SignedComputationOutputs<WithdrawBetOutput>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let result = match output.verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account) {
+            Ok(result) => result,
+            Err(_) => {
+                // Hand the position back so the bettor can retry or keep the bet
+                ctx.accounts.pool_state.pending_computations = ctx.accounts.pool_state.pending_computations.saturating_sub(1);
+                ctx.accounts.user_position.status = PositionStatus::Processed;
+                ctx.accounts.user_portfolio.set_status(ctx.accounts.user_position.key(), PositionStatus::Processed);
+
+                emit!(BetWithdrawalAborted {
+                    market: ctx.accounts.market.key(),
+                    position: ctx.accounts.user_position.key(),
+                    computation_id: ctx.accounts.user_position.computation_id,
+                });
+
+                return Ok(());
+            }
+        };
+
+        // The pool moved while the withdrawal was in flight; hand the position back so it can retry
+        if ctx.accounts.pool_state.state_version != ctx.accounts.user_position.pool_version {
+            ctx.accounts.pool_state.pending_computations = ctx.accounts.pool_state.pending_computations.saturating_sub(1);
+            ctx.accounts.user_position.status = PositionStatus::Processed;
+            ctx.accounts.user_portfolio.set_status(ctx.accounts.user_position.key(), PositionStatus::Processed);
+
+            emit!(PoolStateConflict {
+                market: ctx.accounts.market.key(),
+                position: ctx.accounts.user_position.key(),
+                read_version: ctx.accounts.user_position.pool_version,
+                current_version: ctx.accounts.pool_state.state_version,
+            });
+
+            return Ok(());
+        }
+
+        let pool_state = &mut ctx.accounts.pool_state;
+        pool_state.set_encrypted_state(result.field_0.nonce, &result.field_0.ciphertexts);
+        pool_state.state_version = pool_state.state_version.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+        pool_state.last_updated = clock.unix_timestamp;
+        pool_state.last_computation_id = ctx.accounts.user_position.computation_id;
+        pool_state.pending_computations = pool_state.pending_computations.saturating_sub(1);
+
+        let previous_commitment = ctx.accounts.market.state_commitment;
+        let commitment = pool_state.commitment(&previous_commitment);
+
+        emit!(PoolStateUpdated {
+            market: ctx.accounts.market.key(),
+            state_version: pool_state.state_version,
+            nonce: pool_state.nonce,
+            encrypted_state: pool_state.encrypted_state.clone(),
+            previous_commitment,
+            commitment,
+            updated_at: clock.unix_timestamp,
+        });
+
+        let market = &mut ctx.accounts.market;
+        market.state_commitment = commitment;
+
+        let deposit_amount = ctx.accounts.user_position.deposit_amount;
+        let exit_fee = market.exit_fee(deposit_amount)?;
+        let refund_amount = deposit_amount.checked_sub(exit_fee).ok_or(DarkPoolError::Underflow)?;
+
+        market.fees_accrued = market.fees_accrued.checked_add(exit_fee).ok_or(DarkPoolError::Overflow)?;
+        market.record_outflow(refund_amount)?;
+        market.release_liability(deposit_amount, ctx.accounts.user_position.locked_odds())?;
+        market.pooled_positions = market.pooled_positions.saturating_sub(1);
+
+        let market_key = market.key();
+        let seeds = &[VAULT_SEED, market_key.as_ref(), &[market.vault_bump]];
         let signer_seeds = &[&seeds[..]];
 
         let transfer_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             Transfer {
                 from: ctx.accounts.vault.to_account_info(),
-                to: ctx.accounts.claimer_token_account.to_account_info(),
+                to: ctx.accounts.bettor_token_account.to_account_info(),
                 authority: ctx.accounts.vault.to_account_info(),
             },
             signer_seeds,
         );
-        token::transfer(transfer_ctx, payout_amount)?;
+        token::transfer(transfer_ctx, refund_amount)?;
 
-        position.status = PositionStatus::Claimed;
+        let position = &mut ctx.accounts.user_position;
+        position.status = PositionStatus::Withdrawn;
         position.claimed_at = Some(clock.unix_timestamp);
+        ctx.accounts.user_portfolio.set_status(position.key(), position.status);
 
-        emit!(PayoutClaimed {
+        emit!(BetWithdrawn {
             market: market.key(),
             position: position.key(),
-            user: position.owner,
-            amount: payout_amount,
+            bettor: position.owner,
+            refund_amount,
+            exit_fee,
+            state_version: pool_state.state_version,
         });
 
         Ok(())
     }
 
-    pub fn cancel_market(ctx: Context<CancelMarket>) -> Result<()> {
+    pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
         let market = &mut ctx.accounts.market;
-        let clock = Clock::get()?;
+        let amount = market.fees_accrued;
+
+        require!(amount > 0, DarkPoolError::NoFeesToCollect);
+
+        market.fees_accrued = 0;
+        market.record_outflow(amount)?;
+
+        let market_key = market.key();
+        let seeds = &[VAULT_SEED, market_key.as_ref(), &[market.vault_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.authority_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(transfer_ctx, amount)?;
+
+        emit!(FeesCollected {
+            market: market.key(),
+            authority: ctx.accounts.authority.key(),
+            amount,
+        });
 
+        Ok(())
+    }
+
+    pub fn fund_house(ctx: Context<FundHouse>, amount: u64) -> Result<()> {
+        require!(amount > 0, DarkPoolError::InvalidBetAmount);
         require!(
-            market.status == MarketStatus::Created
-                || market.status == MarketStatus::Open
-                || market.status == MarketStatus::BettingClosed,
+            matches!(ctx.accounts.market.pricing_mode, PricingMode::FixedOdds { .. }),
+            DarkPoolError::FixedOddsRequired
+        );
+        require!(
+            matches!(ctx.accounts.market.status, MarketStatus::Created | MarketStatus::Open),
             DarkPoolError::InvalidMarketStatus
         );
 
-        market.status = MarketStatus::Cancelled;
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.authority_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        );
+        token::transfer(transfer_ctx, amount)?;
 
-        emit!(MarketCancelled {
+        let market = &mut ctx.accounts.market;
+        market.house_funding = market.house_funding.checked_add(amount).ok_or(DarkPoolError::Overflow)?;
+
+        emit!(HouseFunded {
             market: market.key(),
-            cancelled_at: clock.unix_timestamp,
+            authority: ctx.accounts.authority.key(),
+            amount,
+            house_funding: market.house_funding,
         });
 
         Ok(())
     }
 
-    pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
-        let market = &ctx.accounts.market;
-        let position = &mut ctx.accounts.user_position;
-        let clock = Clock::get()?;
-
-        require!(position.can_claim_refund(), DarkPoolError::AlreadyClaimed);
+    pub fn withdraw_house_funds(ctx: Context<WithdrawHouseFunds>, amount: u64) -> Result<()> {
+        require!(amount > 0, DarkPoolError::InvalidBetAmount);
 
-        let refund_amount = position.deposit_amount;
+        let market = &mut ctx.accounts.market;
+        market.withdraw_house_funding(amount)?;
 
-        let market_id_bytes = market.market_id.to_le_bytes();
-        let seeds = &[VAULT_SEED, market_id_bytes.as_ref(), &[market.vault_bump]];
+        let market_key = market.key();
+        let seeds = &[VAULT_SEED, market_key.as_ref(), &[market.vault_bump]];
         let signer_seeds = &[&seeds[..]];
 
         let transfer_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             Transfer {
                 from: ctx.accounts.vault.to_account_info(),
-                to: ctx.accounts.claimer_token_account.to_account_info(),
+                to: ctx.accounts.authority_token_account.to_account_info(),
                 authority: ctx.accounts.vault.to_account_info(),
             },
             signer_seeds,
         );
-        token::transfer(transfer_ctx, refund_amount)?;
+        token::transfer(transfer_ctx, amount)?;
 
-        position.status = PositionStatus::Refunded;
-        position.claimed_at = Some(clock.unix_timestamp);
+        emit!(HouseFundsWithdrawn {
+            market: market.key(),
+            authority: ctx.accounts.authority.key(),
+            amount,
+            house_funding: market.house_funding,
+            house_liability: market.house_liability,
+        });
 
-        emit!(RefundClaimed {
+        Ok(())
+    }
+
+    pub fn sweep_house_funds(ctx: Context<SweepHouseFunds>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        require!(
+            matches!(market.pricing_mode, PricingMode::FixedOdds { .. }),
+            DarkPoolError::FixedOddsRequired
+        );
+        require!(market.payouts_computed == market.pooled_positions, DarkPoolError::PayoutsOutstanding);
+
+        let amount = market.sweepable_house_funds();
+        require!(amount > 0, DarkPoolError::NothingToSweep);
+        market.record_outflow(amount)?;
+
+        if market.status != MarketStatus::Settled {
+            market.status = MarketStatus::Settled;
+            ctx.accounts.market_index.set_status(market.key(), market.status)?;
+        }
+
+        let market_key = market.key();
+        let seeds = &[VAULT_SEED, market_key.as_ref(), &[market.vault_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.authority_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(transfer_ctx, amount)?;
+
+        emit!(HouseFundsSwept {
             market: market.key(),
-            position: position.key(),
-            user: position.owner,
-            amount: refund_amount,
+            authority: ctx.accounts.authority.key(),
+            amount,
+            unclaimed_payouts: market.unclaimed_payouts,
         });
 
         Ok(())
     }
-}
 
-#[init_computation_definition_accounts("process_bet", payer)]
-#[derive(Accounts)]
-pub struct InitProcessBetCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
+    /// New odds only apply to later bets; existing positions keep the odds they locked.
+    pub fn set_fixed_odds(ctx: Context<SetFixedOdds>, yes_odds_bps: u32, no_odds_bps: u32) -> Result<()> {
+        let market = &mut ctx.accounts.market;
 
-    #[account(mut, address = derive_mxe_pda!())]
-    pub mxe_account: Box<Account<'info, MXEAccount>>,
+        require!(
+            matches!(market.pricing_mode, PricingMode::FixedOdds { .. }),
+            DarkPoolError::FixedOddsRequired
+        );
+        require!(
+            matches!(market.status, MarketStatus::Created | MarketStatus::Open),
+            DarkPoolError::InvalidMarketStatus
+        );
 
-    #[account(mut)]
-    /// CHECK: Validated by Arcium
-    pub comp_def_account: UncheckedAccount<'info>,
+        let pricing_mode = PricingMode::FixedOdds { yes_odds_bps, no_odds_bps };
+        pricing_mode.validate()?;
+        market.pricing_mode = pricing_mode;
 
-    pub arcium_program: Program<'info, Arcium>,
-    pub system_program: Program<'info, System>,
-}
+        emit!(FixedOddsUpdated {
+            market: market.key(),
+            yes_odds_bps,
+            no_odds_bps,
+        });
 
-#[init_computation_definition_accounts("compute_payout", payer)]
-#[derive(Accounts)]
-pub struct InitComputePayoutCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
+        Ok(())
+    }
 
-    #[account(mut, address = derive_mxe_pda!())]
+    pub fn transfer_position(
+        ctx: Context<TransferPosition>,
+        computation_offset: u64,
+        new_owner_pubkey: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
+        require!(ctx.accounts.market.allows_position_transfers(), DarkPoolError::InvalidMarketStatus);
+        require!(ctx.accounts.new_owner.key() != ctx.accounts.owner.key(), DarkPoolError::InvalidTransferRecipient);
+        require!(
+            ctx.accounts.user_position.holder(&ctx.accounts.position_asset)? == ctx.accounts.owner.key(),
+            DarkPoolError::Unauthorized
+        );
+
+        transfer_position_asset(
+            &ctx.accounts.mpl_core_program.to_account_info(),
+            &ctx.accounts.position_asset.to_account_info(),
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.new_owner.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &[],
+        )?;
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let args = ArgBuilder::new()
+            .x25519_pubkey(new_owner_pubkey)
+            .plaintext_u128(nonce)
+            .plaintext_u128(ctx.accounts.user_position.stake_nonce)
+            .encrypted_u64(ctx.accounts.user_position.encrypted_stake[0])
+            .encrypted_u64(ctx.accounts.user_position.encrypted_stake[1])
+            .build();
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![TransferPositionCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[
+                    CallbackAccount { pubkey: ctx.accounts.user_position.key(), is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.new_owner_portfolio.key(), is_writable: true },
+                ],
+            )?],
+            1,
+            0,
+        )?;
+
+        let position_key = ctx.accounts.user_position.key();
+        let from = ctx.accounts.owner.key();
+        let to = ctx.accounts.new_owner.key();
+
+        let position = &mut ctx.accounts.user_position;
+        position.set_owner(to);
+        position.user_pubkey = new_owner_pubkey;
+        position.status = PositionStatus::Transferring;
+        position.computation_id = computation_offset;
+
+        let owner_portfolio = &mut ctx.accounts.owner_portfolio;
+        owner_portfolio.owner = from;
+        owner_portfolio.bump = ctx.bumps.owner_portfolio;
+        owner_portfolio.untrack(position_key);
+        let new_owner_portfolio = &mut ctx.accounts.new_owner_portfolio;
+        new_owner_portfolio.owner = to;
+        new_owner_portfolio.bump = ctx.bumps.new_owner_portfolio;
+        new_owner_portfolio.track(ctx.accounts.market.key(), position_key, PositionStatus::Transferring)?;
+
+        emit!(PositionTransferred {
+            market: ctx.accounts.market.key(),
+            position: position_key,
+            from,
+            to,
+            price: 0,
+            computation_id: computation_offset,
+        });
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "transfer_position")]
+    pub fn transfer_position_callback(
+        ctx: Context<TransferPositionCallback>,
+        output: SignedComputationOutputs<TransferPositionOutput>,
+    ) -> Result<()> {
+        let result = match output.verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account) {
+            Ok(result) => result,
+            Err(_) => {
+                // The asset has already moved; the stake is untouched, so the new owner keeps a live
+                // position and can recover their bet view through disclose_position
+                let position = &mut ctx.accounts.user_position;
+                position.status = PositionStatus::Processed;
+                UserPortfolio::update_status(&ctx.accounts.user_portfolio, position.owner, position.key(), PositionStatus::Processed)?;
+
+                emit!(PositionTransferAborted {
+                    market: position.market,
+                    position: position.key(),
+                    owner: position.owner,
+                    computation_id: position.computation_id,
+                });
+
+                return Ok(());
+            }
+        };
+
+        let position = &mut ctx.accounts.user_position;
+        position.encrypted_bet = result
+            .field_0
+            .ciphertexts
+            .iter()
+            .flat_map(|chunk| chunk.iter().copied())
+            .collect();
+        position.nonce = result.field_0.nonce;
+        position.status = PositionStatus::Processed;
+        UserPortfolio::update_status(&ctx.accounts.user_portfolio, position.owner, position.key(), PositionStatus::Processed)?;
+
+        Ok(())
+    }
+
+    pub fn disclose_position(
+        ctx: Context<DisclosePosition>,
+        computation_offset: u64,
+        viewer_pubkey: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
+        require!(
+            ctx.accounts.user_position.holder(&ctx.accounts.position_asset)? == ctx.accounts.owner.key(),
+            DarkPoolError::Unauthorized
+        );
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let args = ArgBuilder::new()
+            .x25519_pubkey(viewer_pubkey)
+            .plaintext_u128(nonce)
+            .plaintext_u128(ctx.accounts.user_position.stake_nonce)
+            .encrypted_u64(ctx.accounts.user_position.encrypted_stake[0])
+            .encrypted_u64(ctx.accounts.user_position.encrypted_stake[1])
+            .build();
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![DisclosePositionCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[CallbackAccount { pubkey: ctx.accounts.user_position.key(), is_writable: false }],
+            )?],
+            1,
+            0,
+        )?;
+
+        // Kept apart from computation_id so a payout or withdrawal queued meanwhile can't orphan this callback
+        ctx.accounts.user_position.disclosure_computation_id = computation_offset;
+
+        emit!(PositionDisclosureRequested {
+            market: ctx.accounts.user_position.market,
+            position: ctx.accounts.user_position.key(),
+            owner: ctx.accounts.owner.key(),
+            viewer_pubkey,
+            computation_id: computation_offset,
+        });
+
+        Ok(())
+    }
+
+    // The stake is only re-encrypted for the viewer; nothing on the position changes
+    #[arcium_callback(encrypted_ix = "disclose_position")]
+    pub fn disclose_position_callback(
+        ctx: Context<DisclosePositionCallback>,
+        output: SignedComputationOutputs<DisclosePositionOutput>,
+    ) -> Result<()> {
+        let result = output
+            .verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account)
+            .map_err(|_| DarkPoolError::ComputationAborted)?;
+
+        emit!(PositionDisclosed {
+            market: ctx.accounts.user_position.market,
+            position: ctx.accounts.user_position.key(),
+            computation: ctx.accounts.computation_account.key(),
+            nonce: result.field_0.nonce,
+            encrypted_stake: result.field_0.ciphertexts,
+        });
+
+        Ok(())
+    }
+
+    pub fn list_position(ctx: Context<ListPosition>, price: u64) -> Result<()> {
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
+        require!(ctx.accounts.market.allows_position_transfers(), DarkPoolError::InvalidMarketStatus);
+        require!(price > 0, DarkPoolError::InvalidListingPrice);
+        require!(
+            ctx.accounts.user_position.holder(&ctx.accounts.position_asset)? == ctx.accounts.seller.key(),
+            DarkPoolError::Unauthorized
+        );
+
+        transfer_position_asset(
+            &ctx.accounts.mpl_core_program.to_account_info(),
+            &ctx.accounts.position_asset.to_account_info(),
+            &ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.listing.to_account_info(),
+            &ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &[],
+        )?;
+
+        let listing = &mut ctx.accounts.listing;
+        listing.market = ctx.accounts.market.key();
+        listing.position = ctx.accounts.user_position.key();
+        listing.seller = ctx.accounts.seller.key();
+        listing.price = price;
+        listing.created_at = Clock::get()?.unix_timestamp;
+        listing.bump = ctx.bumps.listing;
+
+        ctx.accounts.user_position.set_owner(ctx.accounts.seller.key());
+        ctx.accounts.user_position.status = PositionStatus::Listed;
+
+        let seller_portfolio = &mut ctx.accounts.seller_portfolio;
+        seller_portfolio.owner = listing.seller;
+        seller_portfolio.bump = ctx.bumps.seller_portfolio;
+        seller_portfolio.track(listing.market, listing.position, PositionStatus::Listed)?;
+
+        emit!(PositionListed {
+            market: listing.market,
+            position: listing.position,
+            seller: listing.seller,
+            price,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_listing(ctx: Context<CancelListing>) -> Result<()> {
+        let position_key = ctx.accounts.user_position.key();
+        let listing_seeds: &[&[u8]] = &[LISTING_SEED, position_key.as_ref(), &[ctx.accounts.listing.bump]];
+        transfer_position_asset(
+            &ctx.accounts.mpl_core_program.to_account_info(),
+            &ctx.accounts.position_asset.to_account_info(),
+            &ctx.accounts.listing.to_account_info(),
+            &ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &[listing_seeds],
+        )?;
+
+        ctx.accounts.user_position.status = PositionStatus::Processed;
+
+        emit!(ListingCancelled {
+            market: ctx.accounts.listing.market,
+            position: ctx.accounts.listing.position,
+            seller: ctx.accounts.seller.key(),
+        });
+
+        Ok(())
+    }
+
+    pub fn fill_listing(
+        ctx: Context<FillListing>,
+        computation_offset: u64,
+        buyer_pubkey: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
+        require!(ctx.accounts.market.allows_position_transfers(), DarkPoolError::InvalidMarketStatus);
+        require!(ctx.accounts.buyer.key() != ctx.accounts.listing.seller, DarkPoolError::InvalidTransferRecipient);
+
+        let price = ctx.accounts.listing.price;
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.buyer_token_account.to_account_info(),
+                to: ctx.accounts.seller_token_account.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        );
+        token::transfer(transfer_ctx, price)?;
+
+        let position_key = ctx.accounts.user_position.key();
+        let listing_seeds: &[&[u8]] = &[LISTING_SEED, position_key.as_ref(), &[ctx.accounts.listing.bump]];
+        transfer_position_asset(
+            &ctx.accounts.mpl_core_program.to_account_info(),
+            &ctx.accounts.position_asset.to_account_info(),
+            &ctx.accounts.listing.to_account_info(),
+            &ctx.accounts.buyer.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &[listing_seeds],
+        )?;
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let args = ArgBuilder::new()
+            .x25519_pubkey(buyer_pubkey)
+            .plaintext_u128(nonce)
+            .plaintext_u128(ctx.accounts.user_position.stake_nonce)
+            .encrypted_u64(ctx.accounts.user_position.encrypted_stake[0])
+            .encrypted_u64(ctx.accounts.user_position.encrypted_stake[1])
+            .build();
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![TransferPositionCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[
+                    CallbackAccount { pubkey: ctx.accounts.user_position.key(), is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.buyer_portfolio.key(), is_writable: true },
+                ],
+            )?],
+            1,
+            0,
+        )?;
+
+        let seller = ctx.accounts.listing.seller;
+        let buyer = ctx.accounts.buyer.key();
+
+        let position = &mut ctx.accounts.user_position;
+        position.set_owner(buyer);
+        position.user_pubkey = buyer_pubkey;
+        position.status = PositionStatus::Transferring;
+        position.computation_id = computation_offset;
+
+        ctx.accounts.seller_portfolio.untrack(position_key);
+        let buyer_portfolio = &mut ctx.accounts.buyer_portfolio;
+        buyer_portfolio.owner = buyer;
+        buyer_portfolio.bump = ctx.bumps.buyer_portfolio;
+        buyer_portfolio.track(ctx.accounts.market.key(), position_key, PositionStatus::Transferring)?;
+
+        emit!(PositionTransferred {
+            market: ctx.accounts.market.key(),
+            position: position_key,
+            from: seller,
+            to: buyer,
+            price,
+            computation_id: computation_offset,
+        });
+
+        Ok(())
+    }
+
+    pub fn reveal_odds_snapshot(ctx: Context<RevealOddsSnapshot>, computation_offset: u64) -> Result<()> {
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
+        let clock = Clock::get()?;
+
+        require!(ctx.accounts.market.odds_reveal_interval > 0, DarkPoolError::OddsRevealDisabled);
+        require!(
+            ctx.accounts.market.status == MarketStatus::Open || ctx.accounts.market.status == MarketStatus::BettingClosed,
+            DarkPoolError::InvalidMarketStatus
+        );
+        require!(ctx.accounts.market.odds_reveal_due(clock.unix_timestamp), DarkPoolError::OddsRevealTooSoon);
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let args = ArgBuilder::new()
+            .plaintext_u128(ctx.accounts.pool_state.nonce)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext(0)?)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext(1)?)
+            .build();
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![RevealOddsSnapshotCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[CallbackAccount { pubkey: ctx.accounts.market.key(), is_writable: true }],
+            )?],
+            1,
+            0,
+        )?;
+
+        // Claimed at queue time so the interval also rate-limits in-flight reveals
+        ctx.accounts.market.last_odds_reveal_ts = clock.unix_timestamp;
+        // The MPC reads the ciphertexts as of now, so that is the version the snapshot describes
+        ctx.accounts.market.pending_odds_version = ctx.accounts.pool_state.state_version;
+        ctx.accounts.market.pending_odds_computation = computation_offset;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "reveal_odds_snapshot")]
+    pub fn reveal_odds_snapshot_callback(
+        ctx: Context<RevealOddsSnapshotCallback>,
+        output: SignedComputationOutputs<RevealOddsSnapshotOutput>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let result = output
+            .verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account)
+            .map_err(|_| DarkPoolError::ComputationAborted)?;
+
+        let entry = OddsSnapshotEntry {
+            yes_share_bps: result.field_0,
+            state_version: ctx.accounts.market.pending_odds_version,
+            revealed_at: clock.unix_timestamp,
+        };
+        ctx.accounts.market.record_odds_snapshot(entry);
+
+        emit!(OddsSnapshot {
+            market: ctx.accounts.market.key(),
+            yes_share_bps: entry.yes_share_bps,
+            state_version: entry.state_version,
+            revealed_at: entry.revealed_at,
+        });
+
+        Ok(())
+    }
+
+    pub fn open_commit_reveal_market(ctx: Context<OpenCommitRevealMarket>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+
+        require!(market.status == MarketStatus::Created, DarkPoolError::InvalidMarketStatus);
+        require!(market.kind == MarketKind::CommitReveal, DarkPoolError::InvalidMarketKind);
+
+        market.status = MarketStatus::Open;
+        ctx.accounts.market_index.set_status(market.key(), market.status)?;
+
+        emit!(MarketOpened {
+            market: market.key(),
+            seed_position: Pubkey::default(),
+            seed_amount: 0,
+            opened_at: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn commit_bet(
+        ctx: Context<CommitBet>,
+        commitment: [u8; 32],
+        deposit_amount: u64,
+        payout_destination: Option<Pubkey>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        require!(ctx.accounts.market.status == MarketStatus::Open, DarkPoolError::MarketNotOpen);
+        require!(ctx.accounts.market.kind == MarketKind::CommitReveal, DarkPoolError::InvalidMarketKind);
+        require!(clock.unix_timestamp >= ctx.accounts.market.betting_start_ts, DarkPoolError::BettingNotStarted);
+        require!(clock.unix_timestamp < ctx.accounts.market.betting_end_ts, DarkPoolError::BettingEnded);
+        require!(deposit_amount > 0, DarkPoolError::InvalidBetAmount);
+        require!(deposit_amount >= ctx.accounts.market.min_bet, DarkPoolError::BetBelowMinimum);
+        require!(deposit_amount <= ctx.accounts.market.max_bet, DarkPoolError::BetAboveMaximum);
+
+        let total_deposited = ctx.accounts.market.total_deposited.checked_add(deposit_amount).ok_or(DarkPoolError::Overflow)?;
+        require!(total_deposited <= ctx.accounts.market.max_pool, DarkPoolError::PoolCapExceeded);
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.bettor_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.bettor.to_account_info(),
+            },
+        );
+        token::transfer(transfer_ctx, deposit_amount)?;
+
+        let position_key = ctx.accounts.user_position.key();
+        let asset_seeds: &[&[u8]] = &[POSITION_ASSET_SEED, position_key.as_ref(), &[ctx.bumps.position_asset]];
+        mint_position_asset(
+            &ctx.accounts.mpl_core_program.to_account_info(),
+            &ctx.accounts.position_asset.to_account_info(),
+            &ctx.accounts.market.to_account_info(),
+            &ctx.accounts.bettor.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.market.market_id,
+            deposit_amount,
+            asset_seeds,
+        )?;
+
+        let position = &mut ctx.accounts.user_position;
+        let market_key = ctx.accounts.market.key();
+        position.market = market_key;
+        position.owner = ctx.accounts.bettor.key();
+        position.encrypted_bet = vec![];
+        position.user_pubkey = [0u8; 32];
+        position.nonce = 0;
+        position.bet_commitment = Some(commitment);
+        position.encrypted_stake = [[0u8; 32]; 2];
+        position.stake_nonce = 0;
+        position.kind = PositionKind::Bettor;
+        position.asset = ctx.accounts.position_asset.key();
+        position.payout_destination = payout_destination;
+        position.delegate = None;
+        position.deposit_amount = deposit_amount;
+        (position.locked_yes_odds_bps, position.locked_no_odds_bps) = ctx.accounts.market.pricing_mode.odds();
+        position.payout_amount = 0;
+        position.encrypted_payout = None;
+        position.payout_nonce = 0;
+        position.status = PositionStatus::Pending;
+        position.computation_id = 0;
+        position.pool_version = 0;
+        position.disclosure_computation_id = 0;
+        position.audited = false;
+        position.revealed_outcome = None;
+        position.revealed_amount = None;
+        position.bump = ctx.bumps.user_position;
+        position.created_at = clock.unix_timestamp;
+        position.processed_at = None;
+        position.claimed_at = None;
+
+        let portfolio = &mut ctx.accounts.user_portfolio;
+        portfolio.owner = ctx.accounts.bettor.key();
+        portfolio.bump = ctx.bumps.user_portfolio;
+        portfolio.track(market_key, position_key, PositionStatus::Pending)?;
+
+        ctx.accounts.market.total_positions = ctx.accounts.market.total_positions.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+        ctx.accounts.market.unrevealed_bets = ctx.accounts.market.unrevealed_bets.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+        ctx.accounts.market.total_deposited = total_deposited;
+
+        emit!(BetCommitted {
+            market: market_key,
+            position: position_key,
+            bettor: ctx.accounts.bettor.key(),
+            deposit_amount,
+            commitment,
+        });
+
+        Ok(())
+    }
+
+    pub fn reveal_bet(ctx: Context<RevealBet>, outcome: u8, amount: u64, salt: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        let market = &mut ctx.accounts.market;
+        let position = &mut ctx.accounts.user_position;
+
+        require!(market.kind == MarketKind::CommitReveal, DarkPoolError::InvalidMarketKind);
+        require!(
+            market.status == MarketStatus::Open || market.status == MarketStatus::BettingClosed,
+            DarkPoolError::InvalidMarketStatus
+        );
+        require!(clock.unix_timestamp >= market.betting_end_ts, DarkPoolError::BettingNotEnded);
+        require!(clock.unix_timestamp < market.reveal_window_end(), DarkPoolError::RevealWindowClosed);
+        require!(outcome <= 1, DarkPoolError::InvalidOutcome);
+        require!(
+            position.holder(&ctx.accounts.position_asset)? == ctx.accounts.bettor.key(),
+            DarkPoolError::Unauthorized
+        );
+
+        // The holder reveals, but the commitment stays bound to the bettor who made it
+        let commitment = UserPosition::commitment_hash(&market.key(), &position.owner, outcome, amount, &salt);
+        require!(position.bet_commitment == Some(commitment), DarkPoolError::CommitmentMismatch);
+
+        let staked = market.revealed_stake(outcome, amount, position.deposit_amount);
+        if outcome == 1 {
+            market.revealed_yes_pool = market.revealed_yes_pool.checked_add(staked).ok_or(DarkPoolError::Overflow)?;
+        } else {
+            market.revealed_no_pool = market.revealed_no_pool.checked_add(staked).ok_or(DarkPoolError::Overflow)?;
+        }
+
+        // Whatever the reveal doesn't stake is forfeited like an unopened commitment
+        let unstaked = position.deposit_amount.saturating_sub(staked);
+        market.forfeited_pool = market.forfeited_pool.checked_add(unstaked).ok_or(DarkPoolError::Overflow)?;
+        market.unrevealed_bets = market.unrevealed_bets.saturating_sub(1);
+        market.pooled_positions = market.pooled_positions.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+
+        position.revealed_outcome = Some(outcome);
+        position.revealed_amount = Some(staked);
+        position.status = PositionStatus::Processed;
+        position.processed_at = Some(clock.unix_timestamp);
+
+        emit!(BetRevealed {
+            market: market.key(),
+            position: position.key(),
+            outcome,
+            amount: staked,
+        });
+
+        Ok(())
+    }
+
+    pub fn compute_revealed_payout(ctx: Context<ComputeRevealedPayout>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let position = &mut ctx.accounts.user_position;
+
+        require!(market.kind == MarketKind::CommitReveal, DarkPoolError::InvalidMarketKind);
+        // Every forfeit adds to the winners' share, so payouts wait until they are all in
+        require!(market.unrevealed_bets == 0, DarkPoolError::UnrevealedBetsPending);
+
+        let outcome = position.revealed_outcome.ok_or(DarkPoolError::PositionNotProcessed)?;
+        let amount = position.revealed_amount.ok_or(DarkPoolError::PositionNotProcessed)?;
+        let payout_amount = market.revealed_payout(outcome, amount)?;
+
+        market.settle_liability(position.deposit_amount, position.locked_odds(), payout_amount)?;
+        position.payout_amount = payout_amount;
+        position.status = PositionStatus::PayoutComputed;
+        UserPortfolio::update_status(
+            &ctx.accounts.user_portfolio,
+            position.owner,
+            position.key(),
+            PositionStatus::PayoutComputed,
+        )?;
+        market.payouts_computed = market.payouts_computed.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+
+        emit!(PayoutComputed {
+            market: market.key(),
+            position: position.key(),
+            user: position.owner,
+            payout_amount,
+        });
+
+        Ok(())
+    }
+
+    // Deposits behind commitments that were never opened are paid out to the winners. Anyone can
+    // forfeit once the reveal window has closed, so payouts never wait on the authority.
+    pub fn forfeit_bet(ctx: Context<ForfeitBet>) -> Result<()> {
+        let clock = Clock::get()?;
+        let market = &mut ctx.accounts.market;
+        let position = &mut ctx.accounts.user_position;
+
+        require!(market.kind == MarketKind::CommitReveal, DarkPoolError::InvalidMarketKind);
+        require!(clock.unix_timestamp >= market.reveal_window_end(), DarkPoolError::RevealWindowOpen);
+
+        market.forfeited_pool = market.forfeited_pool.checked_add(position.deposit_amount).ok_or(DarkPoolError::Overflow)?;
+        market.unrevealed_bets = market.unrevealed_bets.saturating_sub(1);
+        position.status = PositionStatus::Forfeited;
+
+        emit!(BetForfeited {
+            market: market.key(),
+            position: position.key(),
+            deposit_amount: position.deposit_amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn close_betting(ctx: Context<CloseBetting>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+
+        require!(market.status == MarketStatus::Open, DarkPoolError::InvalidMarketStatus);
+        require!(clock.unix_timestamp >= market.betting_end_ts, DarkPoolError::BettingNotEnded);
+
+        market.status = MarketStatus::BettingClosed;
+        ctx.accounts.market_index.set_status(market.key(), market.status)?;
+
+        emit!(BettingClosed {
+            market: market.key(),
+            total_positions: market.total_positions,
+            closed_at: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn resolve_market(ctx: Context<ResolveMarket>, winning_outcome: u8) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+
+        require!(
+            market.status == MarketStatus::Open || market.status == MarketStatus::BettingClosed,
+            DarkPoolError::InvalidMarketStatus
+        );
+        require!(clock.unix_timestamp >= market.betting_end_ts, DarkPoolError::BettingNotEnded);
+        require!(winning_outcome <= 1, DarkPoolError::InvalidOutcome);
+        if market.kind == MarketKind::CommitReveal {
+            require!(clock.unix_timestamp >= market.reveal_window_end(), DarkPoolError::RevealWindowOpen);
+        }
+
+        market.winning_outcome = Some(winning_outcome);
+        market.status = MarketStatus::Resolved;
+        market.resolved_at = Some(clock.unix_timestamp);
+        ctx.accounts.market_index.set_status(market.key(), market.status)?;
+
+        emit!(MarketResolved {
+            market: market.key(),
+            winning_outcome,
+            resolved_at: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn compute_payout(ctx: Context<ComputePayout>, computation_offset: u64) -> Result<()> {
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
+        let winning_outcome = ctx.accounts.market.winning_outcome.ok_or(DarkPoolError::MarketNotResolved)?;
+
+        // Revealing the amount is what exposes a winner, so only the holder may do it on private markets
+        if ctx.accounts.market.private_payouts {
+            require!(
+                ctx.accounts.user_position.holder(&ctx.accounts.position_asset)? == ctx.accounts.payer.key(),
+                DarkPoolError::PrivatePayoutMarket
+            );
+        }
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let args = ArgBuilder::new()
+            .plaintext_u128(ctx.accounts.user_position.stake_nonce)
+            .encrypted_u64(ctx.accounts.user_position.encrypted_stake[0])
+            .encrypted_u64(ctx.accounts.user_position.encrypted_stake[1])
+            .plaintext_u128(ctx.accounts.pool_state.nonce)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext(0)?)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext(1)?)
+            .plaintext_u8(winning_outcome)
+            .plaintext_u64(ctx.accounts.user_position.locked_yes_odds_bps as u64)
+            .plaintext_u64(ctx.accounts.user_position.locked_no_odds_bps as u64)
+            .build();
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ComputePayoutCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[
+                    CallbackAccount { pubkey: ctx.accounts.market.key(), is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.user_position.key(), is_writable: true },
+                    CallbackAccount {
+                        pubkey: UserPortfolio::address_for(&ctx.accounts.user_position.owner),
+                        is_writable: true,
+                    },
+                ],
+            )?],
+            1,
+            0,
+        )?;
+
+        ctx.accounts.user_position.status = PositionStatus::PayoutPending;
+        ctx.accounts.user_position.computation_id = computation_offset;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "compute_payout")]
+    pub fn compute_payout_callback(
+        ctx: Context<ComputePayoutCallback>,
+        output: SignedComputationOutputs<ComputePayoutOutput>,
+    ) -> Result<()> {
+        let result = match output.verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account) {
+            Ok(result) => result,
+            Err(_) => {
+                // Release the position so the payout can be queued again
+                ctx.accounts.user_position.status = PositionStatus::Processed;
+
+                emit!(PayoutComputationAborted {
+                    market: ctx.accounts.market.key(),
+                    position: ctx.accounts.user_position.key(),
+                    computation_id: ctx.accounts.user_position.computation_id,
+                });
+
+                return Ok(());
+            }
+        };
+
+        let payout_amount = result.field_0;
+
+        ctx.accounts.market.settle_liability(
+            ctx.accounts.user_position.deposit_amount,
+            ctx.accounts.user_position.locked_odds(),
+            payout_amount,
+        )?;
+        ctx.accounts.user_position.payout_amount = payout_amount;
+        ctx.accounts.user_position.status = PositionStatus::PayoutComputed;
+        UserPortfolio::update_status(
+            &ctx.accounts.user_portfolio,
+            ctx.accounts.user_position.owner,
+            ctx.accounts.user_position.key(),
+            PositionStatus::PayoutComputed,
+        )?;
+        ctx.accounts.market.payouts_computed = ctx.accounts.market.payouts_computed.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+
+        emit!(PayoutComputed {
+            market: ctx.accounts.market.key(),
+            position: ctx.accounts.user_position.key(),
+            user: ctx.accounts.user_position.owner,
+            payout_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Writes the payout into a shared claim page as a note only `encrypted_secret` can draw
+    /// from, and closes the position out. The holder also receives the amount encrypted.
+    pub fn compute_private_payout(
+        ctx: Context<ComputePrivatePayout>,
+        computation_offset: u64,
+        encrypted_secret: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
+        require!(
+            ctx.accounts.user_position.holder(&ctx.accounts.position_asset)? == ctx.accounts.owner.key(),
+            DarkPoolError::Unauthorized
+        );
+        require!(!ctx.accounts.claim_page.withdrawal_in_flight(), DarkPoolError::ClaimInFlight);
+        // Each payout folds into the market's running totals, so only one can be in flight
+        require!(ctx.accounts.market.private_payout_computation == 0, DarkPoolError::PrivatePayoutInFlight);
+        let winning_outcome = ctx.accounts.market.winning_outcome.ok_or(DarkPoolError::MarketNotResolved)?;
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let market = &ctx.accounts.market;
+        let first_payout = market.payouts_computed == 0;
+        let last_payout = market.is_last_private_payout();
+
+        let args = ArgBuilder::new()
+            .x25519_pubkey(ctx.accounts.user_position.user_pubkey)
+            .plaintext_u128(nonce)
+            .encrypted_u128(encrypted_secret)
+            .plaintext_u128(ctx.accounts.user_position.stake_nonce)
+            .encrypted_u64(ctx.accounts.user_position.encrypted_stake[0])
+            .encrypted_u64(ctx.accounts.user_position.encrypted_stake[1])
+            .plaintext_u128(ctx.accounts.pool_state.nonce)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext(0)?)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext(1)?);
+        let args = if first_payout {
+            args.plaintext_u128(ctx.accounts.pool_state.nonce)
+                .encrypted_u64(ctx.accounts.pool_state.ciphertext(0)?)
+                .encrypted_u64(ctx.accounts.pool_state.ciphertext(1)?)
+        } else {
+            args.plaintext_u128(market.private_totals_nonce)
+                .encrypted_u64(market.private_totals[0])
+                .encrypted_u64(market.private_totals[1])
+        };
+        let args = args
+            .plaintext_u8(first_payout as u8)
+            .plaintext_u8(last_payout as u8)
+            .plaintext_u64(ctx.accounts.user_position.deposit_amount)
+            .plaintext_u8(winning_outcome)
+            .plaintext_u64(ctx.accounts.user_position.locked_yes_odds_bps as u64)
+            .plaintext_u64(ctx.accounts.user_position.locked_no_odds_bps as u64)
+            .build();
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ComputePrivatePayoutCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[
+                    CallbackAccount { pubkey: ctx.accounts.market.key(), is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.user_position.key(), is_writable: true },
+                    CallbackAccount {
+                        pubkey: UserPortfolio::address_for(&ctx.accounts.user_position.owner),
+                        is_writable: true,
+                    },
+                    CallbackAccount { pubkey: ctx.accounts.claim_page.key(), is_writable: true },
+                ],
+            )?],
+            1,
+            0,
+        )?;
+
+        let market = &mut ctx.accounts.market;
+        let slot = market.private_claims as usize % CLAIM_PAGE_SLOTS;
+        let claim_page = &mut ctx.accounts.claim_page;
+        claim_page.market = market.key();
+        claim_page.page = market.private_claims / CLAIM_PAGE_SLOTS as u32;
+        claim_page.bump = ctx.bumps.claim_page;
+        claim_page.positions[slot] = ctx.accounts.user_position.key();
+        claim_page.assigned += 1;
+        claim_page.pending += 1;
+        market.private_claims = market.private_claims.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+        market.private_payout_computation = computation_offset;
+
+        ctx.accounts.user_position.status = PositionStatus::PayoutPending;
+        ctx.accounts.user_position.computation_id = computation_offset;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "compute_private_payout")]
+    pub fn compute_private_payout_callback(
+        ctx: Context<ComputePrivatePayoutCallback>,
+        output: SignedComputationOutputs<ComputePrivatePayoutOutput>,
+    ) -> Result<()> {
+        let claim_page = &mut ctx.accounts.claim_page;
+        let slot = claim_page.slot_of(&ctx.accounts.user_position.key()).ok_or(DarkPoolError::InvalidPosition)?;
+        claim_page.pending -= 1;
+        ctx.accounts.market.private_payout_computation = 0;
+
+        let result = match output.verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account) {
+            Ok(result) => result,
+            Err(_) => {
+                // The slot stays empty and a retry is given a fresh one
+                claim_page.positions[slot] = Pubkey::default();
+                ctx.accounts.user_position.status = PositionStatus::Processed;
+
+                emit!(PayoutComputationAborted {
+                    market: ctx.accounts.market.key(),
+                    position: ctx.accounts.user_position.key(),
+                    computation_id: ctx.accounts.user_position.computation_id,
+                });
+
+                return Ok(());
+            }
+        };
+
+        let payout = result.field_0.field_0;
+        let note = result.field_0.field_1;
+        let running = result.field_0.field_2;
+        claim_page.notes[slot] = note.ciphertexts;
+        claim_page.note_nonces[slot] = note.nonce;
+        claim_page.filled |= 1 << slot;
+
+        // Closed out here: the payout now belongs to the note, not the position
+        let position = &mut ctx.accounts.user_position;
+        position.encrypted_payout = Some(payout.ciphertexts[0]);
+        position.payout_nonce = payout.nonce;
+        position.status = PositionStatus::Claimed;
+        position.claimed_at = Some(Clock::get()?.unix_timestamp);
+        UserPortfolio::update_status(&ctx.accounts.user_portfolio, position.owner, position.key(), PositionStatus::Claimed)?;
+
+        let market = &mut ctx.accounts.market;
+        market.record_private_payout(position.deposit_amount, position.locked_odds())?;
+        market.private_totals = running.ciphertexts;
+        market.private_totals_nonce = running.nonce;
+        if market.is_last_private_payout() {
+            market.settle_private_payouts(result.field_0.field_3, result.field_0.field_4)?;
+        }
+        market.payouts_computed = market.payouts_computed.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+
+        emit!(PrivatePayoutComputed {
+            market: position.market,
+            position: position.key(),
+            claim_page: claim_page.page,
+            nonce: payout.nonce,
+            encrypted_payout: payout.ciphertexts[0],
+        });
+
+        Ok(())
+    }
+
+    /// Draws `amount` from whichever note on the page `encrypted_secret` opens. Any signer may
+    /// submit it, so neither the position nor its holder appears in the withdrawal.
+    #[allow(clippy::too_many_arguments)]
+    pub fn withdraw_private_claim(
+        ctx: Context<WithdrawPrivateClaim>,
+        computation_offset: u64,
+        _page: u32,
+        encrypted_secret: [u8; 32],
+        pubkey: [u8; 32],
+        nonce: u128,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, DarkPoolError::InvalidBetAmount);
+        let market = &ctx.accounts.market;
+        let claim_page = &ctx.accounts.claim_page;
+        require!(!claim_page.withdrawal_in_flight(), DarkPoolError::ClaimInFlight);
+        require!(
+            claim_page.is_settled(market.payouts_computed == market.pooled_positions),
+            DarkPoolError::ClaimPageOpen
+        );
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let mut args = ArgBuilder::new()
+            .x25519_pubkey(pubkey)
+            .plaintext_u128(nonce)
+            .encrypted_u128(encrypted_secret);
+        for slot in 0..CLAIM_PAGE_SLOTS {
+            args = args
+                .plaintext_u128(claim_page.note_nonces[slot])
+                .encrypted_u128(claim_page.notes[slot][0])
+                .encrypted_u64(claim_page.notes[slot][1]);
+        }
+        let args = args.plaintext_u8(claim_page.filled).plaintext_u64(amount).build();
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![WithdrawPrivateClaimCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[
+                    CallbackAccount { pubkey: ctx.accounts.market.key(), is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.claim_page.key(), is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.destination.key(), is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.vault.key(), is_writable: true },
+                    CallbackAccount { pubkey: ctx.accounts.token_program.key(), is_writable: false },
+                ],
+            )?],
+            1,
+            0,
+        )?;
+
+        ctx.accounts.claim_page.computation_id = computation_offset;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "withdraw_private_claim")]
+    pub fn withdraw_private_claim_callback(
+        ctx: Context<WithdrawPrivateClaimCallback>,
+        output: SignedComputationOutputs<WithdrawPrivateClaimOutput>,
+    ) -> Result<()> {
+        let claim_page = &mut ctx.accounts.claim_page;
+        claim_page.computation_id = 0;
+
+        // The notes are only replaced on success, so an abort leaves every balance withdrawable
+        let result = match output.verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account) {
+            Ok(result) => result,
+            Err(_) => return Ok(()),
+        };
+
+        let notes = [result.field_0.field_0, result.field_0.field_1, result.field_0.field_2, result.field_0.field_3];
+        for (slot, note) in notes.into_iter().enumerate() {
+            if claim_page.is_filled(slot) {
+                claim_page.notes[slot] = note.ciphertexts;
+                claim_page.note_nonces[slot] = note.nonce;
+            }
+        }
+
+        let approved = result.field_0.field_4;
+        if approved > 0 {
+            let market = &mut ctx.accounts.market;
+            market.record_private_claim(approved)?;
+
+            let market_key = market.key();
+            let seeds = &[VAULT_SEED, market_key.as_ref(), &[market.vault_bump]];
+            let signer_seeds = &[&seeds[..]];
+
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::transfer(transfer_ctx, approved)?;
+        }
+
+        emit!(PrivateClaimWithdrawn {
+            market: ctx.accounts.market.key(),
+            claim_page: claim_page.page,
+        });
+
+        Ok(())
+    }
+
+    pub fn compute_payouts_batch(ctx: Context<ComputePayoutsBatch>, computation_offset: u64) -> Result<()> {
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
+        let winning_outcome = ctx.accounts.market.winning_outcome.ok_or(DarkPoolError::MarketNotResolved)?;
+        require!(!ctx.accounts.market.private_payouts, DarkPoolError::PrivatePayoutMarket);
+        require!(ctx.accounts.market.pricing_mode == PricingMode::Parimutuel, DarkPoolError::ParimutuelRequired);
+
+        let count = ctx.remaining_accounts.len();
+        require!(count > 0 && count <= PAYOUT_BATCH_SIZE, DarkPoolError::InvalidPayoutBatch);
+
+        let market_key = ctx.accounts.market.key();
+        let mut positions = Vec::with_capacity(count);
+        for info in ctx.remaining_accounts.iter() {
+            require_keys_eq!(*info.owner, crate::ID, DarkPoolError::InvalidPosition);
+            let mut data = info.try_borrow_mut_data()?;
+            let mut position = UserPosition::try_deserialize(&mut &data[..])?;
+            require_keys_eq!(position.market, market_key, DarkPoolError::InvalidPosition);
+            require!(position.status == PositionStatus::Processed, DarkPoolError::PositionNotProcessed);
+
+            position.status = PositionStatus::PayoutPending;
+            position.computation_id = computation_offset;
+            position.try_serialize(&mut &mut data[..])?;
+            positions.push(position);
+        }
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let mut args = ArgBuilder::new();
+        for slot in 0..PAYOUT_BATCH_SIZE {
+            let position = positions.get(slot).unwrap_or(&positions[0]);
+            args = args
+                .plaintext_u128(position.stake_nonce)
+                .encrypted_u64(position.encrypted_stake[0])
+                .encrypted_u64(position.encrypted_stake[1]);
+        }
+        let args = args
+            .plaintext_u128(ctx.accounts.pool_state.nonce)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext(0)?)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext(1)?)
+            .plaintext_u8(winning_outcome)
+            .plaintext_u8(count as u8)
+            .build();
+
+        let mut callback_accounts = vec![CallbackAccount { pubkey: market_key, is_writable: true }];
+        callback_accounts.extend(
+            ctx.remaining_accounts
+                .iter()
+                .map(|info| CallbackAccount { pubkey: info.key(), is_writable: true }),
+        );
+        callback_accounts.extend(positions.iter().map(|position| CallbackAccount {
+            pubkey: UserPortfolio::address_for(&position.owner),
+            is_writable: true,
+        }));
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ComputePayoutsBatchCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &callback_accounts,
+            )?],
+            1,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "compute_payouts_batch")]
+    pub fn compute_payouts_batch_callback(
+        ctx: Context<ComputePayoutsBatchCallback>,
+        output: SignedComputationOutputs<ComputePayoutsBatchOutput>,
+    ) -> Result<()> {
+        let market_key = ctx.accounts.market.key();
+
+        // Each position's portfolio follows the batch of positions
+        let (position_infos, portfolio_infos) = ctx.remaining_accounts.split_at(ctx.remaining_accounts.len() / 2);
+        let mut positions = Vec::with_capacity(position_infos.len());
+        for info in position_infos {
+            require_keys_eq!(*info.owner, crate::ID, DarkPoolError::InvalidPosition);
+            let position = UserPosition::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            require_keys_eq!(position.market, market_key, DarkPoolError::InvalidPosition);
+            require!(position.status == PositionStatus::PayoutPending, DarkPoolError::InvalidPosition);
+            positions.push(position);
+        }
+
+        // Every slot must have been queued by the computation reporting back
+        let computation_id = positions.first().ok_or(DarkPoolError::InvalidPayoutBatch)?.computation_id;
+        require!(
+            positions.iter().all(|position| position.computation_id == computation_id),
+            DarkPoolError::ComputationMismatch
+        );
+        require_keys_eq!(
+            ctx.accounts.computation_account.key(),
+            derive_comp_pda!(computation_id, ctx.accounts.mxe_account, DarkPoolError::ClusterNotSet),
+            DarkPoolError::ComputationMismatch
+        );
+
+        let result = match output.verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account) {
+            Ok(result) => result,
+            Err(_) => {
+                for (info, position) in position_infos.iter().zip(positions.iter_mut()) {
+                    position.status = PositionStatus::Processed;
+                    position.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+                    emit!(PayoutComputationAborted {
+                        market: market_key,
+                        position: info.key(),
+                        computation_id,
+                    });
+                }
+
+                return Ok(());
+            }
+        };
+
+        let payouts = [
+            result.field_0.field_0,
+            result.field_0.field_1,
+            result.field_0.field_2,
+            result.field_0.field_3,
+        ];
+
+        let slots = position_infos.iter().zip(portfolio_infos).zip(positions.iter_mut()).zip(payouts);
+        for (((info, portfolio_info), position), payout_amount) in slots {
+            ctx.accounts.market.settle_liability(position.deposit_amount, position.locked_odds(), payout_amount)?;
+            position.payout_amount = payout_amount;
+            position.status = PositionStatus::PayoutComputed;
+            position.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+            UserPortfolio::update_status(portfolio_info, position.owner, info.key(), PositionStatus::PayoutComputed)?;
+            ctx.accounts.market.payouts_computed = ctx.accounts.market.payouts_computed.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+
+            emit!(PayoutComputed {
+                market: market_key,
+                position: info.key(),
+                user: position.owner,
+                payout_amount,
+            });
+        }
+
+        emit!(PayoutBatchProcessed {
+            market: market_key,
+            payouts_computed: ctx.accounts.market.payouts_computed,
+            pooled_positions: ctx.accounts.market.pooled_positions,
+        });
+
+        Ok(())
+    }
+
+    pub fn audit_pool_chunk(ctx: Context<AuditPoolChunk>, computation_offset: u64) -> Result<()> {
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
+        require!(!ctx.accounts.pool_audit.is_attested(), DarkPoolError::AuditAlreadyAttested);
+        require!(!ctx.accounts.pool_audit.in_flight, DarkPoolError::AuditInProgress);
+
+        let count = ctx.remaining_accounts.len();
+        require!(count > 0 && count <= AUDIT_BATCH_SIZE, DarkPoolError::InvalidAuditBatch);
+
+        let market_key = ctx.accounts.market.key();
+        let mut positions = Vec::with_capacity(count);
+        for info in ctx.remaining_accounts.iter() {
+            require_keys_eq!(*info.owner, crate::ID, DarkPoolError::InvalidPosition);
+            require!(!positions.iter().any(|(key, _)| *key == info.key()), DarkPoolError::PositionAlreadyAudited);
+
+            let position = UserPosition::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            require_keys_eq!(position.market, market_key, DarkPoolError::InvalidPosition);
+            require!(position.counts_toward_pool(), DarkPoolError::PositionNotProcessed);
+            require!(!position.audited, DarkPoolError::PositionAlreadyAudited);
+            positions.push((info.key(), position));
+        }
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let mut args = ArgBuilder::new();
+        for slot in 0..AUDIT_BATCH_SIZE {
+            let (_, position) = positions.get(slot).unwrap_or(&positions[0]);
+            args = args
+                .plaintext_u128(position.stake_nonce)
+                .encrypted_u64(position.encrypted_stake[0])
+                .encrypted_u64(position.encrypted_stake[1]);
+        }
+
+        let audit = &ctx.accounts.pool_audit;
+        let first_chunk = audit.positions_counted == 0;
+        let args = if first_chunk {
+            args.plaintext_u128(ctx.accounts.pool_state.nonce)
+                .encrypted_u64(ctx.accounts.pool_state.ciphertext(0)?)
+                .encrypted_u64(ctx.accounts.pool_state.ciphertext(1)?)
+        } else {
+            args.plaintext_u128(audit.running_nonce)
+                .encrypted_u64(audit.running_totals[0])
+                .encrypted_u64(audit.running_totals[1])
+        };
+        let args = args.plaintext_u8(count as u8).plaintext_u8(first_chunk as u8).build();
+
+        let mut callback_accounts = vec![CallbackAccount { pubkey: ctx.accounts.pool_audit.key(), is_writable: true }];
+        callback_accounts.extend(
+            ctx.remaining_accounts
+                .iter()
+                .map(|info| CallbackAccount { pubkey: info.key(), is_writable: true }),
+        );
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![AuditPoolChunkCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &callback_accounts,
+            )?],
+            1,
+            0,
+        )?;
+
+        let audit = &mut ctx.accounts.pool_audit;
+        audit.market = market_key;
+        audit.bump = ctx.bumps.pool_audit;
+        audit.in_flight = true;
+        audit.computation_id = computation_offset;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "audit_pool_chunk")]
+    pub fn audit_pool_chunk_callback(
+        ctx: Context<AuditPoolChunkCallback>,
+        output: SignedComputationOutputs<AuditPoolChunkOutput>,
+    ) -> Result<()> {
+        let audit = &mut ctx.accounts.pool_audit;
+        audit.in_flight = false;
+
+        // Nothing was folded; the same positions can simply be submitted again
+        let result = match output.verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account) {
+            Ok(result) => result,
+            Err(_) => return Ok(()),
+        };
+
+        for info in ctx.remaining_accounts.iter() {
+            require_keys_eq!(*info.owner, crate::ID, DarkPoolError::InvalidPosition);
+            let mut data = info.try_borrow_mut_data()?;
+            let mut position = UserPosition::try_deserialize(&mut &data[..])?;
+            require_keys_eq!(position.market, audit.market, DarkPoolError::InvalidPosition);
+
+            position.audited = true;
+            position.try_serialize(&mut &mut data[..])?;
+        }
+
+        audit.running_totals = result.field_0.ciphertexts;
+        audit.running_nonce = result.field_0.nonce;
+        audit.positions_counted = audit
+            .positions_counted
+            .checked_add(ctx.remaining_accounts.len() as u32)
+            .ok_or(DarkPoolError::Overflow)?;
+
+        Ok(())
+    }
+
+    pub fn attest_pool_audit(ctx: Context<AttestPoolAudit>, computation_offset: u64) -> Result<()> {
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
+        let audit = &ctx.accounts.pool_audit;
+        require!(!audit.is_attested(), DarkPoolError::AuditAlreadyAttested);
+        require!(!audit.in_flight, DarkPoolError::AuditInProgress);
+        require!(audit.positions_counted > 0, DarkPoolError::AuditEmpty);
+        // Attestation is final, so it waits until every stake in the pool has been summed
+        require!(
+            audit.positions_counted == ctx.accounts.market.pooled_positions,
+            DarkPoolError::AuditIncomplete
+        );
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let args = ArgBuilder::new()
+            .plaintext_u128(audit.running_nonce)
+            .encrypted_u64(audit.running_totals[0])
+            .encrypted_u64(audit.running_totals[1])
+            .plaintext_u128(ctx.accounts.pool_state.nonce)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext(0)?)
+            .encrypted_u64(ctx.accounts.pool_state.ciphertext(1)?)
+            .build();
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![AttestPoolAuditCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[
+                    CallbackAccount { pubkey: ctx.accounts.market.key(), is_writable: false },
+                    CallbackAccount { pubkey: ctx.accounts.pool_state.key(), is_writable: false },
+                    CallbackAccount { pubkey: ctx.accounts.pool_audit.key(), is_writable: true },
+                ],
+            )?],
+            1,
+            0,
+        )?;
+
+        ctx.accounts.pool_audit.in_flight = true;
+        ctx.accounts.pool_audit.computation_id = computation_offset;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "attest_pool_audit")]
+    pub fn attest_pool_audit_callback(
+        ctx: Context<AttestPoolAuditCallback>,
+        output: SignedComputationOutputs<AttestPoolAuditOutput>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let audit = &mut ctx.accounts.pool_audit;
+        audit.in_flight = false;
+
+        let result = match output.verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account) {
+            Ok(result) => result,
+            Err(_) => return Ok(()),
+        };
+
+        audit.consistent = Some(result.field_0);
+        audit.attested_at = Some(clock.unix_timestamp);
+
+        emit!(MarketIntegrityAttested {
+            market: ctx.accounts.market.key(),
+            consistent: result.field_0,
+            positions_counted: audit.positions_counted,
+            state_version: ctx.accounts.pool_state.state_version,
+            state_commitment: ctx.accounts.market.state_commitment,
+            attested_at: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn reveal_position(ctx: Context<RevealPosition>, computation_offset: u64) -> Result<()> {
+        require!(ctx.accounts.market.kind == MarketKind::Confidential, DarkPoolError::InvalidMarketKind);
+        require!(ctx.accounts.market.reveal_after_resolution, DarkPoolError::PositionRevealDisabled);
+        require!(!ctx.accounts.market.private_payouts, DarkPoolError::PrivatePayoutMarket);
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let args = ArgBuilder::new()
+            .plaintext_u128(ctx.accounts.user_position.stake_nonce)
+            .encrypted_u64(ctx.accounts.user_position.encrypted_stake[0])
+            .encrypted_u64(ctx.accounts.user_position.encrypted_stake[1])
+            .build();
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![RevealPositionCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[CallbackAccount { pubkey: ctx.accounts.user_position.key(), is_writable: true }],
+            )?],
+            1,
+            0,
+        )?;
+
+        ctx.accounts.user_position.computation_id = computation_offset;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "reveal_position")]
+    pub fn reveal_position_callback(
+        ctx: Context<RevealPositionCallback>,
+        output: SignedComputationOutputs<RevealPositionOutput>,
+    ) -> Result<()> {
+        let result = output
+            .verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account)
+            .map_err(|_| DarkPoolError::ComputationAborted)?;
+
+        let (outcome, amount) = (result.field_0.field_0, result.field_0.field_1);
+
+        let position = &mut ctx.accounts.user_position;
+        position.revealed_outcome = Some(outcome);
+        position.revealed_amount = Some(amount);
+
+        emit!(PositionRevealed {
+            market: position.market,
+            position: position.key(),
+            owner: position.owner,
+            outcome,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_payout_destination(
+        ctx: Context<SetPayoutDestination>,
+        payout_destination: Option<Pubkey>,
+        delegate: Option<Pubkey>,
+    ) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        require!(ctx.accounts.user_position.holder(&ctx.accounts.position_asset)? == owner, DarkPoolError::Unauthorized);
+
+        let position = &mut ctx.accounts.user_position;
+        position.set_owner(owner);
+        position.payout_destination = payout_destination;
+        position.delegate = delegate;
+
+        emit!(PayoutRoutingUpdated {
+            market: position.market,
+            position: position.key(),
+            owner,
+            payout_destination,
+            delegate,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_payout(ctx: Context<ClaimPayout>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let position = &mut ctx.accounts.user_position;
+        let clock = Clock::get()?;
+
+        require!(position.payout_amount > 0, DarkPoolError::NoPayout);
+
+        let holder = position.holder(&ctx.accounts.position_asset)?;
+        position.authorize_claim(
+            holder,
+            ctx.accounts.claimer.key(),
+            ctx.accounts.claimer_token_account.key(),
+            ctx.accounts.claimer_token_account.owner,
+        )?;
+
+        let payout_amount = position.payout_amount;
+        market.record_claim(payout_amount)?;
+
+        let market_key = market.key();
+        let seeds = &[VAULT_SEED, market_key.as_ref(), &[market.vault_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.claimer_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(transfer_ctx, payout_amount)?;
+
+        position.status = PositionStatus::Claimed;
+        position.claimed_at = Some(clock.unix_timestamp);
+        ctx.accounts.user_portfolio.settle(position.key(), position.status, holder);
+        position.set_owner(holder);
+
+        emit!(PayoutClaimed {
+            market: market.key(),
+            position: position.key(),
+            user: position.owner,
+            amount: payout_amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn crank_claim(ctx: Context<CrankClaim>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let position = &mut ctx.accounts.user_position;
+        let clock = Clock::get()?;
+
+        require!(position.payout_amount > 0, DarkPoolError::NoPayout);
+        require!(position.holder(&ctx.accounts.position_asset)? == ctx.accounts.owner.key(), DarkPoolError::Unauthorized);
+
+        // Routing set by the current owner wins over their token account
+        let destination = match position.payout_destination.filter(|_| position.owner == ctx.accounts.owner.key()) {
+            Some(payout_destination) => {
+                let account = ctx.accounts.payout_destination.as_ref().ok_or(DarkPoolError::InvalidPayoutDestination)?;
+                require_keys_eq!(account.key(), payout_destination, DarkPoolError::InvalidPayoutDestination);
+                account.to_account_info()
+            }
+            None => ctx.accounts.owner_token_account.to_account_info(),
+        };
+
+        let payout_amount = position.payout_amount;
+        // The cranker keeps a small cut to cover creating the owner's token account
+        let crank_fee = ((payout_amount as u128) * (CRANK_FEE_BPS as u128) / (BPS_DENOMINATOR as u128)) as u64;
+        let owner_amount = payout_amount.checked_sub(crank_fee).ok_or(DarkPoolError::Underflow)?;
+        market.record_claim(payout_amount)?;
+
+        let market_key = market.key();
+        let seeds = &[VAULT_SEED, market_key.as_ref(), &[market.vault_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: destination,
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(transfer_ctx, owner_amount)?;
+
+        if crank_fee > 0 {
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.cranker_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::transfer(transfer_ctx, crank_fee)?;
+        }
+
+        position.status = PositionStatus::Claimed;
+        position.claimed_at = Some(clock.unix_timestamp);
+        ctx.accounts.user_portfolio.settle(position.key(), position.status, ctx.accounts.owner.key());
+        position.set_owner(ctx.accounts.owner.key());
+
+        emit!(PayoutClaimed {
+            market: market.key(),
+            position: position.key(),
+            user: position.owner,
+            amount: owner_amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_market(ctx: Context<CancelMarket>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+
+        require!(
+            market.status == MarketStatus::Created
+                || market.status == MarketStatus::Open
+                || market.status == MarketStatus::BettingClosed,
+            DarkPoolError::InvalidMarketStatus
+        );
+
+        market.status = MarketStatus::Cancelled;
+        ctx.accounts.market_index.set_status(market.key(), market.status)?;
+
+        emit!(MarketCancelled {
+            market: market.key(),
+            cancelled_at: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let position = &mut ctx.accounts.user_position;
+        let clock = Clock::get()?;
+
+        require!(position.can_claim_refund(), DarkPoolError::AlreadyClaimed);
+
+        let holder = position.holder(&ctx.accounts.position_asset)?;
+        position.authorize_claim(
+            holder,
+            ctx.accounts.claimer.key(),
+            ctx.accounts.claimer_token_account.key(),
+            ctx.accounts.claimer_token_account.owner,
+        )?;
+
+        let refund_amount = position.deposit_amount;
+        market.record_outflow(refund_amount)?;
+
+        let market_key = market.key();
+        let seeds = &[VAULT_SEED, market_key.as_ref(), &[market.vault_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.claimer_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(transfer_ctx, refund_amount)?;
+
+        position.status = PositionStatus::Refunded;
+        position.claimed_at = Some(clock.unix_timestamp);
+        ctx.accounts.user_portfolio.settle(position.key(), position.status, holder);
+        position.set_owner(holder);
+
+        emit!(RefundClaimed {
+            market: market.key(),
+            position: position.key(),
+            user: position.owner,
+            amount: refund_amount,
+        });
+
+        Ok(())
+    }
+}
+
+#[init_computation_definition_accounts("process_bet", payer)]
+#[derive(Accounts)]
+pub struct InitProcessBetCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut)]
+    /// CHECK: Validated by Arcium
+    pub comp_def_account: UncheckedAccount<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("compute_payout", payer)]
+#[derive(Accounts)]
+pub struct InitComputePayoutCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut)]
+    /// CHECK: Validated by Arcium
+    pub comp_def_account: UncheckedAccount<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("seed_liquidity", payer)]
+#[derive(Accounts)]
+pub struct InitSeedLiquidityCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut)]
+    /// CHECK: Validated by Arcium
+    pub comp_def_account: UncheckedAccount<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("withdraw_bet", payer)]
+#[derive(Accounts)]
+pub struct InitWithdrawBetCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut)]
+    /// CHECK: Validated by Arcium
+    pub comp_def_account: UncheckedAccount<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("transfer_position", payer)]
+#[derive(Accounts)]
+pub struct InitTransferPositionCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut)]
+    /// CHECK: Validated by Arcium
+    pub comp_def_account: UncheckedAccount<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("disclose_position", payer)]
+#[derive(Accounts)]
+pub struct InitDisclosePositionCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut)]
+    /// CHECK: Validated by Arcium
+    pub comp_def_account: UncheckedAccount<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("reveal_position", payer)]
+#[derive(Accounts)]
+pub struct InitRevealPositionCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut)]
+    /// CHECK: Validated by Arcium
+    pub comp_def_account: UncheckedAccount<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("reveal_odds_snapshot", payer)]
+#[derive(Accounts)]
+pub struct InitRevealOddsSnapshotCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut)]
+    /// CHECK: Validated by Arcium
+    pub comp_def_account: UncheckedAccount<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("process_bet_batch", payer)]
+#[derive(Accounts)]
+pub struct InitProcessBetBatchCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut)]
+    /// CHECK: Validated by Arcium
+    pub comp_def_account: UncheckedAccount<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("compute_private_payout", payer)]
+#[derive(Accounts)]
+pub struct InitComputePrivatePayoutCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut)]
+    /// CHECK: Validated by Arcium
+    pub comp_def_account: UncheckedAccount<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("compute_payouts_batch", payer)]
+#[derive(Accounts)]
+pub struct InitComputePayoutsBatchCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut)]
+    /// CHECK: Validated by Arcium
+    pub comp_def_account: UncheckedAccount<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("audit_pool_chunk", payer)]
+#[derive(Accounts)]
+pub struct InitAuditPoolChunkCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut)]
+    /// CHECK: Validated by Arcium
+    pub comp_def_account: UncheckedAccount<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("attest_pool_audit", payer)]
+#[derive(Accounts)]
+pub struct InitAttestPoolAuditCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut)]
+    /// CHECK: Validated by Arcium
+    pub comp_def_account: UncheckedAccount<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("withdraw_private_claim", payer)]
+#[derive(Accounts)]
+pub struct InitWithdrawPrivateClaimCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut)]
+    /// CHECK: Validated by Arcium
+    pub comp_def_account: UncheckedAccount<'info>,
+
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRegistry<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MarketRegistry::INIT_SPACE,
+        seeds = [REGISTRY_SEED],
+        bump
+    )]
+    pub registry: Account<'info, MarketRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(creator_nonce: u64)]
+pub struct CreateMarket<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mut, seeds = [REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Account<'info, MarketRegistry>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + DarkMarket::INIT_SPACE,
+        // Seeded by the creator rather than the next id so other creators can't invalidate the address
+        seeds = [MARKET_SEED, authority.key().as_ref(), creator_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + EncryptedPoolState::INIT_SPACE,
+        seeds = [POOL_STATE_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub pool_state: Account<'info, EncryptedPoolState>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + MarketIndexPage::INIT_SPACE,
+        seeds = [MARKET_INDEX_SEED, registry.index_page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub market_index: Box<Account<'info, MarketIndexPage>>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = token_mint,
+        token::authority = vault,
+        seeds = [VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenMarket<'info> {
+    #[account(constraint = authority.key() == market.authority @ DarkPoolError::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        mut,
+        constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState
+    )]
+    pub pool_state: Account<'info, EncryptedPoolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_INDEX_SEED, market.index_page.to_le_bytes().as_ref()],
+        bump = market_index.bump
+    )]
+    pub market_index: Account<'info, MarketIndexPage>,
+}
+
+#[queue_computation_accounts("seed_liquidity", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct OpenSeededMarket<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(constraint = authority.key() == market.authority @ DarkPoolError::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(
+        mut,
+        constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState
+    )]
+    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_INDEX_SEED, market.index_page.to_le_bytes().as_ref()],
+        bump = market_index.bump
+    )]
+    pub market_index: Box<Account<'info, MarketIndexPage>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [CREATOR_POSITION_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub seed_position: Box<Account<'info, UserPosition>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserPortfolio::INIT_SPACE,
+        seeds = [PORTFOLIO_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub creator_portfolio: Box<Account<'info, UserPortfolio>>,
+
+    #[account(mut, seeds = [POSITION_ASSET_SEED, seed_position.key().as_ref()], bump)]
+    /// CHECK: Created by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(address = mpl_core::ID)]
+    /// CHECK: Validated by address constraint
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = authority_token_account.owner == authority.key() @ DarkPoolError::InvalidTokenAccountOwner,
+        constraint = authority_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
+    )]
+    pub authority_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = vault.key() == market.vault @ DarkPoolError::InvalidVault
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut, address = derive_mempool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub mempool_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_execpool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub executing_pool: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("seed_liquidity")))]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+
+    #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("seed_liquidity")]
+#[derive(Accounts)]
+pub struct SeedLiquidityCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("seed_liquidity")))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+
+    #[account(address = derive_comp_pda!(seed_position.computation_id, mxe_account, DarkPoolError::ClusterNotSet) @ DarkPoolError::ComputationMismatch)]
+    /// CHECK: Bound to the computation queued for the position
+    pub computation_account: UncheckedAccount<'info>,
+
+    pub cluster_account: Account<'info, Cluster>,
+
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Validated by address constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(mut, constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState)]
+    pub pool_state: Account<'info, EncryptedPoolState>,
+
+    #[account(
+        mut,
+        seeds = [CREATOR_POSITION_SEED, market.key().as_ref()],
+        bump = seed_position.bump
+    )]
+    pub seed_position: Account<'info, UserPosition>,
+
+    #[account(mut, seeds = [PORTFOLIO_SEED, seed_position.owner.as_ref()], bump)]
+    /// CHECK: Updated through UserPortfolio::update_status
+    pub creator_portfolio: UncheckedAccount<'info>,
+}
+
+#[queue_computation_accounts("process_bet", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct PlaceBet<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(
+        mut,
+        constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState,
+        constraint = pool_state.is_initialized @ DarkPoolError::PoolStateNotInitialized
+    )]
+    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [USER_POSITION_SEED, market.key().as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(mut, seeds = [POSITION_ASSET_SEED, user_position.key().as_ref()], bump)]
+    /// CHECK: Created by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(address = mpl_core::ID)]
+    /// CHECK: Validated by address constraint
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserPortfolio::INIT_SPACE,
+        seeds = [PORTFOLIO_SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub user_portfolio: Box<Account<'info, UserPortfolio>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + AuditRecord::INIT_SPACE,
+        seeds = [AUDIT_RECORD_SEED, user_position.key().as_ref()],
+        bump
+    )]
+    pub audit_record: Option<Box<Account<'info, AuditRecord>>>,
+
+    #[account(
+        mut,
+        constraint = bettor_token_account.owner == bettor.key() @ DarkPoolError::InvalidTokenAccountOwner,
+        constraint = bettor_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
+    )]
+    pub bettor_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = vault.key() == market.vault @ DarkPoolError::InvalidVault
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut, address = derive_mempool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub mempool_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_execpool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub executing_pool: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("process_bet")))]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+
+    #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("process_bet")]
+#[derive(Accounts)]
+pub struct ProcessBetCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("process_bet")))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+
+    #[account(address = derive_comp_pda!(user_position.computation_id, mxe_account, DarkPoolError::ClusterNotSet) @ DarkPoolError::ComputationMismatch)]
+    /// CHECK: Bound to the computation queued for the position
+    pub computation_account: UncheckedAccount<'info>,
+
+    pub cluster_account: Account<'info, Cluster>,
+
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Validated by address constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(mut, address = market.pool_state @ DarkPoolError::InvalidPoolState)]
+    pub pool_state: Account<'info, EncryptedPoolState>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.status == PositionStatus::Pending @ DarkPoolError::InvalidPosition
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(mut, seeds = [PORTFOLIO_SEED, user_position.owner.as_ref()], bump)]
+    /// CHECK: Updated through UserPortfolio::update_status
+    pub user_portfolio: UncheckedAccount<'info>,
+}
+
+#[queue_computation_accounts("process_bet", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RequeueBet<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(
+        mut,
+        address = market.pool_state @ DarkPoolError::InvalidPoolState,
+        constraint = pool_state.is_initialized @ DarkPoolError::PoolStateNotInitialized
+    )]
+    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.status == PositionStatus::Pending @ DarkPoolError::InvalidPosition,
+        constraint = user_position.kind == PositionKind::Bettor @ DarkPoolError::InvalidPosition
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut, address = derive_mempool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub mempool_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_execpool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub executing_pool: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("process_bet")))]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+
+    #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[derive(Accounts)]
+pub struct QueueBet<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(
+        constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState,
+        constraint = pool_state.is_initialized @ DarkPoolError::PoolStateNotInitialized
+    )]
+    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [USER_POSITION_SEED, market.key().as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(mut, seeds = [POSITION_ASSET_SEED, user_position.key().as_ref()], bump)]
+    /// CHECK: Created by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(address = mpl_core::ID)]
+    /// CHECK: Validated by address constraint
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserPortfolio::INIT_SPACE,
+        seeds = [PORTFOLIO_SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub user_portfolio: Box<Account<'info, UserPortfolio>>,
+
+    #[account(
+        mut,
+        constraint = bettor_token_account.owner == bettor.key() @ DarkPoolError::InvalidTokenAccountOwner,
+        constraint = bettor_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
+    )]
+    pub bettor_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = vault.key() == market.vault @ DarkPoolError::InvalidVault
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PendingBetQueue::INIT_SPACE,
+        seeds = [PENDING_BETS_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub pending_bets: Box<Account<'info, PendingBetQueue>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("process_bet_batch", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ProcessBetBatch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(
+        mut,
+        constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState,
+        constraint = pool_state.is_initialized @ DarkPoolError::PoolStateNotInitialized
+    )]
+    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
+
+    #[account(
+        mut,
+        seeds = [PENDING_BETS_SEED, market.key().as_ref()],
+        bump = pending_bets.bump
+    )]
+    pub pending_bets: Box<Account<'info, PendingBetQueue>>,
+
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut, address = derive_mempool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub mempool_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_execpool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub executing_pool: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("process_bet_batch")))]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+
+    #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("process_bet_batch")]
+#[derive(Accounts)]
+pub struct ProcessBetBatchCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("process_bet_batch")))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+
+    #[account(address = derive_comp_pda!(pool_state.batch_computation_id, mxe_account, DarkPoolError::ClusterNotSet) @ DarkPoolError::ComputationMismatch)]
+    /// CHECK: Bound to the batch queued on the pool
+    pub computation_account: UncheckedAccount<'info>,
+
+    pub cluster_account: Account<'info, Cluster>,
+
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Validated by address constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(mut, constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState)]
+    pub pool_state: Account<'info, EncryptedPoolState>,
+}
+
+#[queue_computation_accounts("withdraw_bet", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct WithdrawBet<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub bettor: Signer<'info>,
+
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(
+        mut,
+        constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState,
+        constraint = pool_state.is_initialized @ DarkPoolError::PoolStateNotInitialized
+    )]
+    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.status == PositionStatus::Processed @ DarkPoolError::PositionNotProcessed
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(address = user_position.asset @ DarkPoolError::InvalidPositionAsset)]
+    /// CHECK: Validated by address constraint and parsed by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserPortfolio::INIT_SPACE,
+        seeds = [PORTFOLIO_SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub user_portfolio: Box<Account<'info, UserPortfolio>>,
+
+    #[account(
+        constraint = bettor_token_account.owner == bettor.key() @ DarkPoolError::InvalidTokenAccountOwner,
+        constraint = bettor_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
+    )]
+    pub bettor_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(constraint = vault.key() == market.vault @ DarkPoolError::InvalidVault)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut, address = derive_mempool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub mempool_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_execpool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub executing_pool: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("withdraw_bet")))]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+
+    #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("withdraw_bet")]
+#[derive(Accounts)]
+pub struct WithdrawBetCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("withdraw_bet")))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+
+    #[account(address = derive_comp_pda!(user_position.computation_id, mxe_account, DarkPoolError::ClusterNotSet) @ DarkPoolError::ComputationMismatch)]
+    /// CHECK: Bound to the computation queued for the position
+    pub computation_account: UncheckedAccount<'info>,
+
+    pub cluster_account: Account<'info, Cluster>,
+
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Validated by address constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(mut, constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState)]
+    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.status == PositionStatus::Withdrawing @ DarkPoolError::InvalidMarketStatus
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(
+        mut,
+        seeds = [PORTFOLIO_SEED, user_position.owner.as_ref()],
+        bump = user_portfolio.bump
+    )]
+    pub user_portfolio: Box<Account<'info, UserPortfolio>>,
+
+    #[account(
+        mut,
+        constraint = bettor_token_account.owner == user_position.owner @ DarkPoolError::InvalidTokenAccountOwner,
+        constraint = bettor_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
+    )]
+    pub bettor_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, constraint = vault.key() == market.vault @ DarkPoolError::InvalidVault)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    #[account(constraint = authority.key() == market.authority @ DarkPoolError::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        mut,
+        constraint = authority_token_account.owner == authority.key() @ DarkPoolError::InvalidTokenAccountOwner,
+        constraint = authority_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = vault.key() == market.vault @ DarkPoolError::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FundHouse<'info> {
+    #[account(constraint = authority.key() == market.authority @ DarkPoolError::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        mut,
+        constraint = authority_token_account.owner == authority.key() @ DarkPoolError::InvalidTokenAccountOwner,
+        constraint = authority_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = vault.key() == market.vault @ DarkPoolError::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawHouseFunds<'info> {
+    #[account(constraint = authority.key() == market.authority @ DarkPoolError::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        mut,
+        constraint = authority_token_account.owner == authority.key() @ DarkPoolError::InvalidTokenAccountOwner,
+        constraint = authority_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = vault.key() == market.vault @ DarkPoolError::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SweepHouseFunds<'info> {
+    #[account(constraint = authority.key() == market.authority @ DarkPoolError::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = market.status == MarketStatus::Resolved || market.status == MarketStatus::Settled @ DarkPoolError::MarketNotResolved
+    )]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_INDEX_SEED, market.index_page.to_le_bytes().as_ref()],
+        bump = market_index.bump
+    )]
+    pub market_index: Box<Account<'info, MarketIndexPage>>,
+
+    #[account(
+        mut,
+        constraint = authority_token_account.owner == authority.key() @ DarkPoolError::InvalidTokenAccountOwner,
+        constraint = authority_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = vault.key() == market.vault @ DarkPoolError::InvalidVault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetFixedOdds<'info> {
+    #[account(constraint = authority.key() == market.authority @ DarkPoolError::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, DarkMarket>,
+}
+
+#[queue_computation_accounts("transfer_position", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct TransferPosition<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Any wallet can receive a position
+    pub new_owner: UncheckedAccount<'info>,
+
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.status == PositionStatus::Processed @ DarkPoolError::PositionNotProcessed
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(mut, address = user_position.asset @ DarkPoolError::InvalidPositionAsset)]
+    /// CHECK: Validated by address constraint and parsed by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(address = mpl_core::ID)]
+    /// CHECK: Validated by address constraint
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserPortfolio::INIT_SPACE,
+        seeds = [PORTFOLIO_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub owner_portfolio: Box<Account<'info, UserPortfolio>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserPortfolio::INIT_SPACE,
+        seeds = [PORTFOLIO_SEED, new_owner.key().as_ref()],
+        bump
+    )]
+    pub new_owner_portfolio: Box<Account<'info, UserPortfolio>>,
+
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut, address = derive_mempool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub mempool_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_execpool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub executing_pool: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("transfer_position")))]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+
+    #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("transfer_position")]
+#[derive(Accounts)]
+pub struct TransferPositionCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("transfer_position")))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+
+    #[account(address = derive_comp_pda!(user_position.computation_id, mxe_account, DarkPoolError::ClusterNotSet) @ DarkPoolError::ComputationMismatch)]
+    /// CHECK: Bound to the computation queued for the position
+    pub computation_account: UncheckedAccount<'info>,
+
+    pub cluster_account: Account<'info, Cluster>,
+
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Validated by address constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    #[account(mut, constraint = user_position.status == PositionStatus::Transferring @ DarkPoolError::InvalidPosition)]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(mut, seeds = [PORTFOLIO_SEED, user_position.owner.as_ref()], bump)]
+    /// CHECK: Updated through UserPortfolio::update_status
+    pub user_portfolio: UncheckedAccount<'info>,
+}
+
+#[queue_computation_accounts("disclose_position", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct DisclosePosition<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut, constraint = user_position.can_disclose() @ DarkPoolError::NothingToDisclose)]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(address = user_position.market @ DarkPoolError::InvalidPosition)]
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(address = user_position.asset @ DarkPoolError::InvalidPositionAsset)]
+    /// CHECK: Validated by address constraint and parsed by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut, address = derive_mempool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub mempool_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_execpool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub executing_pool: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("disclose_position")))]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+
+    #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("disclose_position")]
+#[derive(Accounts)]
+pub struct DisclosePositionCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("disclose_position")))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+
+    #[account(address = derive_comp_pda!(user_position.disclosure_computation_id, mxe_account, DarkPoolError::ClusterNotSet) @ DarkPoolError::ComputationMismatch)]
+    /// CHECK: Bound to the disclosure queued for the position
+    pub computation_account: UncheckedAccount<'info>,
+
+    pub cluster_account: Account<'info, Cluster>,
+
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Validated by address constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    pub user_position: Account<'info, UserPosition>,
+}
+
+#[derive(Accounts)]
+pub struct ListPosition<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.status == PositionStatus::Processed @ DarkPoolError::PositionNotProcessed
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(mut, address = user_position.asset @ DarkPoolError::InvalidPositionAsset)]
+    /// CHECK: Validated by address constraint and parsed by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(address = mpl_core::ID)]
+    /// CHECK: Validated by address constraint
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + PositionListing::INIT_SPACE,
+        seeds = [LISTING_SEED, user_position.key().as_ref()],
+        bump
+    )]
+    pub listing: Account<'info, PositionListing>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8 + UserPortfolio::INIT_SPACE,
+        seeds = [PORTFOLIO_SEED, seller.key().as_ref()],
+        bump
+    )]
+    pub seller_portfolio: Account<'info, UserPortfolio>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelListing<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = user_position.owner == seller.key() @ DarkPoolError::Unauthorized,
+        constraint = user_position.status == PositionStatus::Listed @ DarkPoolError::InvalidListing
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(mut, address = user_position.asset @ DarkPoolError::InvalidPositionAsset)]
+    /// CHECK: Validated by address constraint and parsed by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(address = mpl_core::ID)]
+    /// CHECK: Validated by address constraint
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = seller,
+        seeds = [LISTING_SEED, user_position.key().as_ref()],
+        bump = listing.bump,
+        constraint = listing.seller == seller.key() @ DarkPoolError::Unauthorized
+    )]
+    pub listing: Account<'info, PositionListing>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("transfer_position", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct FillListing<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub buyer: Signer<'info>,
+
+    #[account(mut, address = listing.seller @ DarkPoolError::InvalidListing)]
+    /// CHECK: Receives the listing rent on close
+    pub seller: UncheckedAccount<'info>,
+
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.owner == listing.seller @ DarkPoolError::InvalidListing,
+        constraint = user_position.status == PositionStatus::Listed @ DarkPoolError::InvalidListing
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(mut, address = user_position.asset @ DarkPoolError::InvalidPositionAsset)]
+    /// CHECK: Validated by address constraint and parsed by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(address = mpl_core::ID)]
+    /// CHECK: Validated by address constraint
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = seller,
+        seeds = [LISTING_SEED, user_position.key().as_ref()],
+        bump = listing.bump
+    )]
+    pub listing: Box<Account<'info, PositionListing>>,
+
+    #[account(
+        mut,
+        seeds = [PORTFOLIO_SEED, listing.seller.as_ref()],
+        bump = seller_portfolio.bump
+    )]
+    pub seller_portfolio: Box<Account<'info, UserPortfolio>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserPortfolio::INIT_SPACE,
+        seeds = [PORTFOLIO_SEED, buyer.key().as_ref()],
+        bump
+    )]
+    pub buyer_portfolio: Box<Account<'info, UserPortfolio>>,
+
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == buyer.key() @ DarkPoolError::InvalidTokenAccountOwner,
+        constraint = buyer_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = seller_token_account.owner == listing.seller @ DarkPoolError::InvalidTokenAccountOwner,
+        constraint = seller_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut, address = derive_mempool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub mempool_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_execpool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub executing_pool: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("transfer_position")))]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+
+    #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("reveal_odds_snapshot", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RevealOddsSnapshot<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(
+        constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState,
+        constraint = pool_state.is_ready() @ DarkPoolError::PoolStateNotInitialized
+    )]
+    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
+
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut, address = derive_mempool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub mempool_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_execpool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub executing_pool: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("reveal_odds_snapshot")))]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+
+    #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("reveal_odds_snapshot")]
+#[derive(Accounts)]
+pub struct RevealOddsSnapshotCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("reveal_odds_snapshot")))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+
+    #[account(address = derive_comp_pda!(market.pending_odds_computation, mxe_account, DarkPoolError::ClusterNotSet) @ DarkPoolError::ComputationMismatch)]
+    /// CHECK: Bound to the snapshot queued on the market
+    pub computation_account: UncheckedAccount<'info>,
+
+    pub cluster_account: Account<'info, Cluster>,
+
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Validated by address constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, DarkMarket>,
+}
+
+#[derive(Accounts)]
+pub struct OpenCommitRevealMarket<'info> {
+    #[account(constraint = authority.key() == market.authority @ DarkPoolError::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_INDEX_SEED, market.index_page.to_le_bytes().as_ref()],
+        bump = market_index.bump
+    )]
+    pub market_index: Box<Account<'info, MarketIndexPage>>,
+}
+
+#[derive(Accounts)]
+pub struct CommitBet<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [USER_POSITION_SEED, market.key().as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(mut, seeds = [POSITION_ASSET_SEED, user_position.key().as_ref()], bump)]
+    /// CHECK: Created by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(address = mpl_core::ID)]
+    /// CHECK: Validated by address constraint
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserPortfolio::INIT_SPACE,
+        seeds = [PORTFOLIO_SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub user_portfolio: Box<Account<'info, UserPortfolio>>,
+
+    #[account(
+        mut,
+        constraint = bettor_token_account.owner == bettor.key() @ DarkPoolError::InvalidTokenAccountOwner,
+        constraint = bettor_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
+    )]
+    pub bettor_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = vault.key() == market.vault @ DarkPoolError::InvalidVault
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealBet<'info> {
+    pub bettor: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.status == PositionStatus::Pending @ DarkPoolError::InvalidPosition
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(address = user_position.asset @ DarkPoolError::InvalidPositionAsset)]
+    /// CHECK: Validated by address constraint and parsed by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ComputeRevealedPayout<'info> {
+    #[account(mut, constraint = market.status == MarketStatus::Resolved @ DarkPoolError::MarketNotResolved)]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.status == PositionStatus::Processed @ DarkPoolError::PositionNotProcessed
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(mut, seeds = [PORTFOLIO_SEED, user_position.owner.as_ref()], bump)]
+    /// CHECK: Updated through UserPortfolio::update_status
+    pub user_portfolio: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ForfeitBet<'info> {
+    #[account(
+        mut,
+        constraint = matches!(
+            market.status,
+            MarketStatus::Open | MarketStatus::BettingClosed | MarketStatus::Resolved | MarketStatus::Settled
+        ) @ DarkPoolError::InvalidMarketStatus
+    )]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.status == PositionStatus::Pending @ DarkPoolError::InvalidPosition,
+        constraint = user_position.bet_commitment.is_some() @ DarkPoolError::InvalidPosition
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+#[derive(Accounts)]
+pub struct CloseBetting<'info> {
+    #[account(constraint = authority.key() == market.authority @ DarkPoolError::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_INDEX_SEED, market.index_page.to_le_bytes().as_ref()],
+        bump = market_index.bump
+    )]
+    pub market_index: Box<Account<'info, MarketIndexPage>>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveMarket<'info> {
+    #[account(constraint = authority.key() == market.authority @ DarkPoolError::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_INDEX_SEED, market.index_page.to_le_bytes().as_ref()],
+        bump = market_index.bump
+    )]
+    pub market_index: Box<Account<'info, MarketIndexPage>>,
+}
+
+#[queue_computation_accounts("compute_payout", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ComputePayout<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(constraint = market.status == MarketStatus::Resolved @ DarkPoolError::MarketNotResolved)]
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState)]
+    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.status == PositionStatus::Processed @ DarkPoolError::InvalidMarketStatus
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(address = user_position.asset @ DarkPoolError::InvalidPositionAsset)]
+    /// CHECK: Validated by address constraint and parsed by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(init_if_needed, space = 9, payer = payer, seeds = [&SIGN_PDA_SEED], bump)]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut, address = derive_mempool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub mempool_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_execpool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub executing_pool: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("compute_payout")))]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+
+    #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("compute_payout")]
+#[derive(Accounts)]
+pub struct ComputePayoutCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("compute_payout")))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+
+    #[account(address = derive_comp_pda!(user_position.computation_id, mxe_account, DarkPoolError::ClusterNotSet) @ DarkPoolError::ComputationMismatch)]
+    /// CHECK: Bound to the computation queued for the position
+    pub computation_account: UncheckedAccount<'info>,
+
+    pub cluster_account: Account<'info, Cluster>,
+
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Validated by address constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    #[account(mut, constraint = market.status == MarketStatus::Resolved @ DarkPoolError::MarketNotResolved)]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.status == PositionStatus::PayoutPending @ DarkPoolError::InvalidPosition
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(mut, seeds = [PORTFOLIO_SEED, user_position.owner.as_ref()], bump)]
+    /// CHECK: Updated through UserPortfolio::update_status
+    pub user_portfolio: UncheckedAccount<'info>,
+}
+
+#[queue_computation_accounts("compute_private_payout", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ComputePrivatePayout<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = market.private_payouts @ DarkPoolError::PrivatePayoutsDisabled,
+        constraint = market.status == MarketStatus::Resolved @ DarkPoolError::MarketNotResolved
+    )]
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(address = market.pool_state @ DarkPoolError::InvalidPoolState)]
+    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.status == PositionStatus::Processed @ DarkPoolError::PositionNotProcessed
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(address = user_position.asset @ DarkPoolError::InvalidPositionAsset)]
+    /// CHECK: Validated by address constraint and parsed by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PrivateClaimPage::INIT_SPACE,
+        seeds = [
+            CLAIM_PAGE_SEED,
+            market.key().as_ref(),
+            (market.private_claims / CLAIM_PAGE_SLOTS as u32).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub claim_page: Box<Account<'info, PrivateClaimPage>>,
+
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut, address = derive_mempool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub mempool_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_execpool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub executing_pool: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("compute_private_payout")))]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+
+    #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("compute_private_payout")]
+#[derive(Accounts)]
+pub struct ComputePrivatePayoutCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("compute_private_payout")))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+
+    #[account(address = derive_comp_pda!(user_position.computation_id, mxe_account, DarkPoolError::ClusterNotSet) @ DarkPoolError::ComputationMismatch)]
+    /// CHECK: Bound to the computation queued for the position
+    pub computation_account: UncheckedAccount<'info>,
+
+    pub cluster_account: Account<'info, Cluster>,
+
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Validated by address constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.status == PositionStatus::PayoutPending @ DarkPoolError::InvalidPosition
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(mut, seeds = [PORTFOLIO_SEED, user_position.owner.as_ref()], bump)]
+    /// CHECK: Updated through UserPortfolio::update_status
+    pub user_portfolio: UncheckedAccount<'info>,
+
+    #[account(mut, constraint = claim_page.market == market.key() @ DarkPoolError::InvalidClaimPage)]
+    pub claim_page: Box<Account<'info, PrivateClaimPage>>,
+}
+
+#[queue_computation_accounts("withdraw_private_claim", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, page: u32)]
+pub struct WithdrawPrivateClaim<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        constraint = market.private_payouts @ DarkPoolError::PrivatePayoutsDisabled,
+        constraint = market.status == MarketStatus::Resolved || market.status == MarketStatus::Settled @ DarkPoolError::MarketNotResolved
+    )]
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(
+        mut,
+        seeds = [CLAIM_PAGE_SEED, market.key().as_ref(), page.to_le_bytes().as_ref()],
+        bump = claim_page.bump
+    )]
+    pub claim_page: Box<Account<'info, PrivateClaimPage>>,
+
+    #[account(constraint = destination.mint == market.token_mint @ DarkPoolError::InvalidTokenMint)]
+    pub destination: Box<Account<'info, TokenAccount>>,
+
+    #[account(constraint = vault.key() == market.vault @ DarkPoolError::InvalidVault)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut, address = derive_mempool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub mempool_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_execpool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub executing_pool: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("withdraw_private_claim")))]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+
+    #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("withdraw_private_claim")]
+#[derive(Accounts)]
+pub struct WithdrawPrivateClaimCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("withdraw_private_claim")))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+
+    #[account(address = derive_comp_pda!(claim_page.computation_id, mxe_account, DarkPoolError::ClusterNotSet) @ DarkPoolError::ComputationMismatch)]
+    /// CHECK: Bound to the withdrawal queued on the claim page
+    pub computation_account: UncheckedAccount<'info>,
+
+    pub cluster_account: Account<'info, Cluster>,
+
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Validated by address constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(mut, constraint = claim_page.market == market.key() @ DarkPoolError::InvalidClaimPage)]
+    pub claim_page: Box<Account<'info, PrivateClaimPage>>,
+
+    #[account(mut, constraint = destination.mint == market.token_mint @ DarkPoolError::InvalidTokenMint)]
+    pub destination: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, constraint = vault.key() == market.vault @ DarkPoolError::InvalidVault)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[queue_computation_accounts("compute_payouts_batch", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ComputePayoutsBatch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(constraint = market.status == MarketStatus::Resolved @ DarkPoolError::MarketNotResolved)]
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState)]
+    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
+
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+
+    #[account(address = derive_mxe_pda!())]
     pub mxe_account: Box<Account<'info, MXEAccount>>,
 
-    #[account(mut)]
-    /// CHECK: Validated by Arcium
-    pub comp_def_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_mempool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub mempool_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_execpool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub executing_pool: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("compute_payouts_batch")))]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+
+    #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("compute_payouts_batch")]
+#[derive(Accounts)]
+pub struct ComputePayoutsBatchCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("compute_payouts_batch")))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+
+    /// CHECK: Bound in the handler to the computation every position in the batch was queued under
+    pub computation_account: UncheckedAccount<'info>,
+
+    pub cluster_account: Account<'info, Cluster>,
+
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Validated by address constraint
+    pub instructions_sysvar: AccountInfo<'info>,
 
-    pub arcium_program: Program<'info, Arcium>,
-    pub system_program: Program<'info, System>,
+    #[account(mut, constraint = market.status == MarketStatus::Resolved @ DarkPoolError::MarketNotResolved)]
+    pub market: Account<'info, DarkMarket>,
 }
 
+#[queue_computation_accounts("audit_pool_chunk", payer)]
 #[derive(Accounts)]
-#[instruction(market_id: u64)]
-pub struct CreateMarket<'info> {
+#[instruction(computation_offset: u64)]
+pub struct AuditPoolChunk<'info> {
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub payer: Signer<'info>,
 
     #[account(
-        init,
-        payer = authority,
-        space = 8 + DarkMarket::INIT_SPACE,
-        seeds = [MARKET_SEED, market_id.to_le_bytes().as_ref()],
-        bump
+        constraint = market.status == MarketStatus::Resolved || market.status == MarketStatus::Settled @ DarkPoolError::MarketNotResolved
     )]
-    pub market: Account<'info, DarkMarket>,
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(address = market.pool_state @ DarkPoolError::InvalidPoolState)]
+    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
 
     #[account(
-        init,
-        payer = authority,
-        space = 8 + EncryptedPoolState::INIT_SPACE,
-        seeds = [POOL_STATE_SEED, market_id.to_le_bytes().as_ref()],
+        init_if_needed,
+        payer = payer,
+        space = 8 + PoolAudit::INIT_SPACE,
+        seeds = [POOL_AUDIT_SEED, market.key().as_ref()],
         bump
     )]
-    pub pool_state: Account<'info, EncryptedPoolState>,
-
-    pub token_mint: Account<'info, Mint>,
+    pub pool_audit: Box<Account<'info, PoolAudit>>,
 
     #[account(
-        init,
-        payer = authority,
-        token::mint = token_mint,
-        token::authority = vault,
-        seeds = [VAULT_SEED, market_id.to_le_bytes().as_ref()],
-        bump
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+
+    #[account(mut, address = derive_mempool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub mempool_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_execpool_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub executing_pool: UncheckedAccount<'info>,
+
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, DarkPoolError::ClusterNotSet))]
+    /// CHECK: Validated by address constraint
+    pub computation_account: UncheckedAccount<'info>,
+
+    #[account(address = derive_comp_def_pda!(comp_def_offset("audit_pool_chunk")))]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+
+    #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Box<Account<'info, FeePool>>,
+
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
 
-    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
 }
 
+#[callback_accounts("audit_pool_chunk")]
 #[derive(Accounts)]
-pub struct OpenMarket<'info> {
-    #[account(constraint = authority.key() == market.authority @ DarkPoolError::Unauthorized)]
-    pub authority: Signer<'info>,
+pub struct AuditPoolChunkCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
 
-    #[account(mut)]
-    pub market: Account<'info, DarkMarket>,
+    #[account(address = derive_comp_def_pda!(comp_def_offset("audit_pool_chunk")))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
 
-    #[account(
-        mut,
-        constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState
-    )]
-    pub pool_state: Account<'info, EncryptedPoolState>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+
+    #[account(address = derive_comp_pda!(pool_audit.computation_id, mxe_account, DarkPoolError::ClusterNotSet) @ DarkPoolError::ComputationMismatch)]
+    /// CHECK: Bound to the computation queued on the audit
+    pub computation_account: UncheckedAccount<'info>,
+
+    pub cluster_account: Account<'info, Cluster>,
+
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Validated by address constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub pool_audit: Account<'info, PoolAudit>,
 }
 
-#[queue_computation_accounts("process_bet", payer)]
+#[queue_computation_accounts("attest_pool_audit", payer)]
 #[derive(Accounts)]
 #[instruction(computation_offset: u64)]
-pub struct PlaceBet<'info> {
+pub struct AttestPoolAudit<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    #[account(mut)]
-    pub bettor: Signer<'info>,
-
-    #[account(mut)]
-    pub market: Box<Account<'info, DarkMarket>>,
-
-    #[account(
-        mut,
-        constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState,
-        constraint = pool_state.is_initialized @ DarkPoolError::PoolStateNotInitialized
-    )]
-    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
-
     #[account(
-        init,
-        payer = payer,
-        space = 8 + UserPosition::INIT_SPACE,
-        seeds = [USER_POSITION_SEED, market.key().as_ref(), bettor.key().as_ref()],
-        bump
+        constraint = market.status == MarketStatus::Resolved || market.status == MarketStatus::Settled @ DarkPoolError::MarketNotResolved
     )]
-    pub user_position: Box<Account<'info, UserPosition>>,
+    pub market: Box<Account<'info, DarkMarket>>,
 
-    #[account(
-        mut,
-        constraint = bettor_token_account.owner == bettor.key() @ DarkPoolError::InvalidTokenAccountOwner,
-        constraint = bettor_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
-    )]
-    pub bettor_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(address = market.pool_state @ DarkPoolError::InvalidPoolState)]
+    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
 
     #[account(
         mut,
-        constraint = vault.key() == market.vault @ DarkPoolError::InvalidVault
+        seeds = [POOL_AUDIT_SEED, market.key().as_ref()],
+        bump = pool_audit.bump
     )]
-    pub vault: Box<Account<'info, TokenAccount>>,
+    pub pool_audit: Box<Account<'info, PoolAudit>>,
 
     #[account(
         init_if_needed,
@@ -578,7 +5327,7 @@ pub struct PlaceBet<'info> {
     /// CHECK: Validated by address constraint
     pub computation_account: UncheckedAccount<'info>,
 
-    #[account(address = derive_comp_def_pda!(comp_def_offset("process_bet")))]
+    #[account(address = derive_comp_def_pda!(comp_def_offset("attest_pool_audit")))]
     pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
 
     #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
@@ -590,32 +5339,23 @@ pub struct PlaceBet<'info> {
     #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
     pub clock_account: Box<Account<'info, ClockAccount>>,
 
-    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub arcium_program: Program<'info, Arcium>,
 }
 
-#[callback_accounts("process_bet")]
+#[callback_accounts("attest_pool_audit")]
 #[derive(Accounts)]
-pub struct ProcessBetCallback<'info> {
-    #[account(mut)]
-    pub market: Account<'info, DarkMarket>,
-
-    #[account(mut, constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState)]
-    pub pool_state: Account<'info, EncryptedPoolState>,
-
-    #[account(mut, constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition)]
-    pub user_position: Account<'info, UserPosition>,
-
+pub struct AttestPoolAuditCallback<'info> {
     pub arcium_program: Program<'info, Arcium>,
 
-    #[account(address = derive_comp_def_pda!(comp_def_offset("process_bet")))]
+    #[account(address = derive_comp_def_pda!(comp_def_offset("attest_pool_audit")))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
 
     #[account(address = derive_mxe_pda!())]
     pub mxe_account: Account<'info, MXEAccount>,
 
-    /// CHECK: Validated by Arcium callback
+    #[account(address = derive_comp_pda!(pool_audit.computation_id, mxe_account, DarkPoolError::ClusterNotSet) @ DarkPoolError::ComputationMismatch)]
+    /// CHECK: Bound to the computation queued on the audit
     pub computation_account: UncheckedAccount<'info>,
 
     pub cluster_account: Account<'info, Cluster>,
@@ -623,47 +5363,48 @@ pub struct ProcessBetCallback<'info> {
     #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
     /// CHECK: Validated by address constraint
     pub instructions_sysvar: AccountInfo<'info>,
-}
-
-#[derive(Accounts)]
-pub struct CloseBetting<'info> {
-    #[account(constraint = authority.key() == market.authority @ DarkPoolError::Unauthorized)]
-    pub authority: Signer<'info>,
 
-    #[account(mut)]
     pub market: Account<'info, DarkMarket>,
-}
 
-#[derive(Accounts)]
-pub struct ResolveMarket<'info> {
-    #[account(constraint = authority.key() == market.authority @ DarkPoolError::Unauthorized)]
-    pub authority: Signer<'info>,
+    #[account(address = market.pool_state @ DarkPoolError::InvalidPoolState)]
+    pub pool_state: Account<'info, EncryptedPoolState>,
 
-    #[account(mut)]
-    pub market: Account<'info, DarkMarket>,
+    #[account(
+        mut,
+        seeds = [POOL_AUDIT_SEED, market.key().as_ref()],
+        bump = pool_audit.bump
+    )]
+    pub pool_audit: Account<'info, PoolAudit>,
 }
 
-#[queue_computation_accounts("compute_payout", payer)]
+#[queue_computation_accounts("reveal_position", payer)]
 #[derive(Accounts)]
 #[instruction(computation_offset: u64)]
-pub struct ComputePayout<'info> {
+pub struct RevealPosition<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    #[account(constraint = market.status == MarketStatus::Resolved @ DarkPoolError::MarketNotResolved)]
+    #[account(
+        constraint = market.status == MarketStatus::Resolved || market.status == MarketStatus::Settled @ DarkPoolError::MarketNotResolved
+    )]
     pub market: Box<Account<'info, DarkMarket>>,
 
-    #[account(constraint = pool_state.market == market.key() @ DarkPoolError::InvalidPoolState)]
-    pub pool_state: Box<Account<'info, EncryptedPoolState>>,
-
     #[account(
-        mut,
         constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
-        constraint = user_position.status == PositionStatus::Processed @ DarkPoolError::InvalidMarketStatus
+        mut,
+        constraint = user_position.counts_toward_pool() @ DarkPoolError::PositionNotProcessed,
+        constraint = !user_position.has_computation_in_flight() @ DarkPoolError::ComputationInFlight,
+        constraint = user_position.revealed_outcome.is_none() @ DarkPoolError::PositionAlreadyRevealed
     )]
     pub user_position: Box<Account<'info, UserPosition>>,
 
-    #[account(init_if_needed, space = 9, payer = payer, seeds = [&SIGN_PDA_SEED], bump)]
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+    )]
     pub sign_pda_account: Account<'info, ArciumSignerAccount>,
 
     #[account(address = derive_mxe_pda!())]
@@ -681,7 +5422,7 @@ pub struct ComputePayout<'info> {
     /// CHECK: Validated by address constraint
     pub computation_account: UncheckedAccount<'info>,
 
-    #[account(address = derive_comp_def_pda!(comp_def_offset("compute_payout")))]
+    #[account(address = derive_comp_def_pda!(comp_def_offset("reveal_position")))]
     pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
 
     #[account(mut, address = derive_cluster_pda!(mxe_account, DarkPoolError::ClusterNotSet))]
@@ -697,24 +5438,19 @@ pub struct ComputePayout<'info> {
     pub arcium_program: Program<'info, Arcium>,
 }
 
-#[callback_accounts("compute_payout")]
+#[callback_accounts("reveal_position")]
 #[derive(Accounts)]
-pub struct ComputePayoutCallback<'info> {
-    #[account(mut, constraint = market.status == MarketStatus::Resolved @ DarkPoolError::MarketNotResolved)]
-    pub market: Account<'info, DarkMarket>,
-
-    #[account(mut, constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition)]
-    pub user_position: Account<'info, UserPosition>,
-
+pub struct RevealPositionCallback<'info> {
     pub arcium_program: Program<'info, Arcium>,
 
-    #[account(address = derive_comp_def_pda!(comp_def_offset("compute_payout")))]
+    #[account(address = derive_comp_def_pda!(comp_def_offset("reveal_position")))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
 
     #[account(address = derive_mxe_pda!())]
     pub mxe_account: Account<'info, MXEAccount>,
 
-    /// CHECK: Validated by Arcium callback
+    #[account(address = derive_comp_pda!(user_position.computation_id, mxe_account, DarkPoolError::ClusterNotSet) @ DarkPoolError::ComputationMismatch)]
+    /// CHECK: Bound to the computation queued for the position
     pub computation_account: UncheckedAccount<'info>,
 
     pub cluster_account: Account<'info, Cluster>,
@@ -722,6 +5458,24 @@ pub struct ComputePayoutCallback<'info> {
     #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
     /// CHECK: Validated by address constraint
     pub instructions_sysvar: AccountInfo<'info>,
+
+    #[account(mut, constraint = user_position.revealed_outcome.is_none() @ DarkPoolError::PositionAlreadyRevealed)]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+#[derive(Accounts)]
+pub struct SetPayoutDestination<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = !user_position.status.is_settled() @ DarkPoolError::AlreadyClaimed
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(address = user_position.asset @ DarkPoolError::InvalidPositionAsset)]
+    /// CHECK: Validated by address constraint and parsed by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
@@ -730,22 +5484,31 @@ pub struct ClaimPayout<'info> {
     pub claimer: Signer<'info>,
 
     #[account(
+        mut,
         constraint = market.status == MarketStatus::Resolved || market.status == MarketStatus::Settled @ DarkPoolError::MarketNotResolved
     )]
     pub market: Account<'info, DarkMarket>,
 
     #[account(
         mut,
-        seeds = [USER_POSITION_SEED, market.key().as_ref(), claimer.key().as_ref()],
-        bump = user_position.bump,
-        constraint = user_position.owner == claimer.key() @ DarkPoolError::Unauthorized,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
         constraint = user_position.status == PositionStatus::PayoutComputed @ DarkPoolError::PayoutNotComputed
     )]
     pub user_position: Account<'info, UserPosition>,
 
+    #[account(address = user_position.asset @ DarkPoolError::InvalidPositionAsset)]
+    /// CHECK: Validated by address constraint and parsed by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [PORTFOLIO_SEED, user_position.owner.as_ref()],
+        bump = user_portfolio.bump
+    )]
+    pub user_portfolio: Account<'info, UserPortfolio>,
+
     #[account(
         mut,
-        constraint = claimer_token_account.owner == claimer.key() @ DarkPoolError::InvalidTokenAccountOwner,
         constraint = claimer_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
     )]
     pub claimer_token_account: Account<'info, TokenAccount>,
@@ -756,6 +5519,70 @@ pub struct ClaimPayout<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+pub struct CrankClaim<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    /// CHECK: Checked against the position asset holder in the handler
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = market.status == MarketStatus::Resolved || market.status == MarketStatus::Settled @ DarkPoolError::MarketNotResolved
+    )]
+    pub market: Box<Account<'info, DarkMarket>>,
+
+    #[account(
+        mut,
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition,
+        constraint = user_position.status == PositionStatus::PayoutComputed @ DarkPoolError::PayoutNotComputed
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(address = user_position.asset @ DarkPoolError::InvalidPositionAsset)]
+    /// CHECK: Validated by address constraint and parsed by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [PORTFOLIO_SEED, user_position.owner.as_ref()],
+        bump = user_portfolio.bump
+    )]
+    pub user_portfolio: Box<Account<'info, UserPortfolio>>,
+
+    #[account(address = market.token_mint @ DarkPoolError::InvalidTokenMint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        associated_token::mint = token_mint,
+        associated_token::authority = owner
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = payout_destination.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
+    )]
+    pub payout_destination: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = cranker_token_account.owner == cranker.key() @ DarkPoolError::InvalidTokenAccountOwner,
+        constraint = cranker_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
+    )]
+    pub cranker_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, constraint = vault.key() == market.vault @ DarkPoolError::InvalidVault)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct CancelMarket<'info> {
     #[account(constraint = authority.key() == market.authority @ DarkPoolError::Unauthorized)]
@@ -763,6 +5590,13 @@ pub struct CancelMarket<'info> {
 
     #[account(mut)]
     pub market: Account<'info, DarkMarket>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_INDEX_SEED, market.index_page.to_le_bytes().as_ref()],
+        bump = market_index.bump
+    )]
+    pub market_index: Box<Account<'info, MarketIndexPage>>,
 }
 
 #[derive(Accounts)]
@@ -770,20 +5604,28 @@ pub struct ClaimRefund<'info> {
     #[account(mut)]
     pub claimer: Signer<'info>,
 
-    #[account(constraint = market.status == MarketStatus::Cancelled @ DarkPoolError::MarketNotCancelled)]
+    #[account(mut, constraint = market.status == MarketStatus::Cancelled @ DarkPoolError::MarketNotCancelled)]
     pub market: Account<'info, DarkMarket>,
 
     #[account(
         mut,
-        seeds = [USER_POSITION_SEED, market.key().as_ref(), claimer.key().as_ref()],
-        bump = user_position.bump,
-        constraint = user_position.owner == claimer.key() @ DarkPoolError::Unauthorized
+        constraint = user_position.market == market.key() @ DarkPoolError::InvalidPosition
     )]
     pub user_position: Account<'info, UserPosition>,
 
+    #[account(address = user_position.asset @ DarkPoolError::InvalidPositionAsset)]
+    /// CHECK: Validated by address constraint and parsed by mpl-core
+    pub position_asset: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [PORTFOLIO_SEED, user_position.owner.as_ref()],
+        bump = user_portfolio.bump
+    )]
+    pub user_portfolio: Account<'info, UserPortfolio>,
+
     #[account(
         mut,
-        constraint = claimer_token_account.owner == claimer.key() @ DarkPoolError::InvalidTokenAccountOwner,
         constraint = claimer_token_account.mint == market.token_mint @ DarkPoolError::InvalidTokenMint
     )]
     pub claimer_token_account: Account<'info, TokenAccount>,
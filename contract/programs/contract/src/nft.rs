@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use mpl_core::accounts::BaseAssetV1;
+use mpl_core::instructions::{CreateV2CpiBuilder, TransferV1CpiBuilder};
+use mpl_core::types::{Attribute, Attributes, Plugin, PluginAuthorityPair};
+
+use crate::errors::DarkPoolError;
+
+// Only public market data goes on the asset; the bet direction stays encrypted
+#[allow(clippy::too_many_arguments)]
+pub fn mint_position_asset<'info>(
+    mpl_core_program: &AccountInfo<'info>,
+    asset: &AccountInfo<'info>,
+    market: &AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    market_id: u64,
+    deposit_amount: u64,
+    asset_signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let attributes = Attributes {
+        attribute_list: vec![
+            Attribute { key: "market".to_string(), value: market.key().to_string() },
+            Attribute { key: "market_id".to_string(), value: market_id.to_string() },
+            Attribute { key: "deposit".to_string(), value: deposit_amount.to_string() },
+        ],
+    };
+
+    CreateV2CpiBuilder::new(mpl_core_program)
+        .asset(asset)
+        .payer(payer)
+        .owner(Some(owner))
+        .update_authority(Some(market))
+        .system_program(system_program)
+        .name(format!("Epoch Position #{}", market_id))
+        .uri(String::new())
+        .plugins(vec![PluginAuthorityPair {
+            plugin: Plugin::Attributes(attributes),
+            authority: None,
+        }])
+        .invoke_signed(&[asset_signer_seeds])?;
+
+    Ok(())
+}
+
+pub fn transfer_position_asset<'info>(
+    mpl_core_program: &AccountInfo<'info>,
+    asset: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    new_owner: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    TransferV1CpiBuilder::new(mpl_core_program)
+        .asset(asset)
+        .payer(payer)
+        .authority(Some(authority))
+        .new_owner(new_owner)
+        .system_program(Some(system_program))
+        .invoke_signed(signer_seeds)?;
+
+    Ok(())
+}
+
+pub fn position_asset_holder(asset: &AccountInfo) -> Result<Pubkey> {
+    require!(asset.owner == &mpl_core::ID, DarkPoolError::InvalidPositionAsset);
+
+    let data = asset.try_borrow_data()?;
+    let asset = BaseAssetV1::from_bytes(&data).map_err(|_| DarkPoolError::InvalidPositionAsset)?;
+
+    Ok(asset.owner)
+}
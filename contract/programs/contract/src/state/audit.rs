@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct PoolAudit {
+    pub market: Pubkey,
+    pub running_totals: [[u8; 32]; 2],
+    pub running_nonce: u128,
+    pub positions_counted: u32,
+    pub in_flight: bool,
+    pub computation_id: u64,
+    pub consistent: Option<bool>,
+    pub attested_at: Option<i64>,
+    pub bump: u8,
+}
+
+/// A position's stake encrypted to the market auditor's key.
+#[account]
+#[derive(InitSpace)]
+pub struct AuditRecord {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub auditor_pubkey: [u8; 32],
+    pub nonce: u128,
+    pub encrypted_stake: [[u8; 32]; 2],
+    pub recorded_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl PoolAudit {
+    pub fn is_attested(&self) -> bool {
+        self.attested_at.is_some()
+    }
+}
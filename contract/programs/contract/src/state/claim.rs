@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::constants::CLAIM_PAGE_SLOTS;
+
+/// Encrypted claim notes for private payouts. Withdrawals only name the page, so a payout
+/// can't be traced to the position whose note it was drawn from.
+#[account]
+#[derive(InitSpace)]
+pub struct PrivateClaimPage {
+    pub market: Pubkey,
+    pub page: u32,
+    pub positions: [Pubkey; CLAIM_PAGE_SLOTS],
+    pub notes: [[[u8; 32]; 2]; CLAIM_PAGE_SLOTS],
+    pub note_nonces: [u128; CLAIM_PAGE_SLOTS],
+    pub filled: u8,
+    pub assigned: u8,
+    pub pending: u8,
+    pub computation_id: u64,
+    pub bump: u8,
+}
+
+impl PrivateClaimPage {
+    pub fn is_filled(&self, slot: usize) -> bool {
+        self.filled & (1 << slot) != 0
+    }
+
+    pub fn slot_of(&self, position: &Pubkey) -> Option<usize> {
+        self.positions.iter().position(|key| key == position)
+    }
+
+    pub fn withdrawal_in_flight(&self) -> bool {
+        self.computation_id != 0
+    }
+
+    /// A page only opens for withdrawals once no note on it can still change.
+    pub fn is_settled(&self, all_payouts_computed: bool) -> bool {
+        self.pending == 0 && (self.assigned as usize == CLAIM_PAGE_SLOTS || all_payouts_computed)
+    }
+}
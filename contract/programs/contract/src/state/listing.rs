@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct PositionListing {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub seller: Pubkey,
+    pub price: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
@@ -1,11 +1,14 @@
 use anchor_lang::prelude::*;
-use crate::constants::MAX_QUESTION_LEN;
+use crate::constants::{BPS_DENOMINATOR, MAX_FIXED_ODDS_BPS, MAX_QUESTION_LEN, ODDS_SNAPSHOT_HISTORY};
+use crate::errors::DarkPoolError;
 
 #[account]
 #[derive(InitSpace)]
 pub struct DarkMarket {
     pub authority: Pubkey,
     pub market_id: u64,
+    pub creator_nonce: u64,
+    pub index_page: u32,
     #[max_len(MAX_QUESTION_LEN)]
     pub question: String,
     pub token_mint: Pubkey,
@@ -14,9 +17,44 @@ pub struct DarkMarket {
     pub betting_start_ts: i64,
     pub betting_end_ts: i64,
     pub resolution_end_ts: i64,
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub max_pool: u64,
+    pub total_deposited: u64,
+    pub total_paid_out: u64,
+    pub exit_fee_bps: u16,
+    pub fees_accrued: u64,
+    pub odds_reveal_interval: i64,
+    pub last_odds_reveal_ts: i64,
+    pub pending_odds_version: u64,
+    pub pending_odds_computation: u64,
+    pub odds_snapshot_count: u32,
+    pub odds_snapshots: [OddsSnapshotEntry; ODDS_SNAPSHOT_HISTORY],
+    pub auditor_pubkey: Option<[u8; 32]>,
+    pub reveal_after_resolution: bool,
+    pub private_payouts: bool,
+    pub kind: MarketKind,
+    pub revealed_yes_pool: u64,
+    pub revealed_no_pool: u64,
+    pub forfeited_pool: u64,
+    pub unrevealed_bets: u32,
+    pub pricing_mode: PricingMode,
+    pub house_funding: u64,
+    pub house_liability: u64,
+    pub house_payouts: u64,
+    pub unclaimed_payouts: u64,
     pub status: MarketStatus,
     pub winning_outcome: Option<u8>,
     pub total_positions: u32,
+    pub pooled_positions: u32,
+    pub payouts_computed: u32,
+    pub private_claims: u32,
+    pub private_totals: [[u8; 32]; 2],
+    pub private_totals_nonce: u128,
+    pub private_payout_computation: u64,
+    pub private_exposure: u64,
+    pub private_withdrawn: u64,
+    pub private_payout_total: Option<u64>,
     pub state_commitment: [u8; 32],
     pub bump: u8,
     pub vault_bump: u8,
@@ -25,8 +63,32 @@ pub struct DarkMarket {
     pub resolved_at: Option<i64>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace, Debug)]
+pub struct OddsSnapshotEntry {
+    pub yes_share_bps: u16,
+    pub state_version: u64,
+    pub revealed_at: i64,
+}
+
+/// Commit-reveal markets keep bets as hash commitments and settle in plaintext without MPC.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug, Default)]
+pub enum MarketKind {
+    #[default]
+    Confidential,
+    CommitReveal,
+}
+
+/// Fixed-odds markets pay winners from house funds at the odds locked when the bet was placed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug, Default)]
+pub enum PricingMode {
+    #[default]
+    Parimutuel,
+    FixedOdds { yes_odds_bps: u32, no_odds_bps: u32 },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug, Default)]
 pub enum MarketStatus {
+    #[default]
     Created,
     Open,
     BettingClosed,
@@ -35,8 +97,235 @@ pub enum MarketStatus {
     Cancelled,
 }
 
-impl Default for MarketStatus {
-    fn default() -> Self {
-        MarketStatus::Created
+impl DarkMarket {
+    pub fn exit_fee(&self, amount: u64) -> Result<u64> {
+        let fee = (amount as u128)
+            .checked_mul(self.exit_fee_bps as u128)
+            .ok_or(DarkPoolError::Overflow)?
+            / BPS_DENOMINATOR as u128;
+        Ok(fee as u64)
+    }
+
+    /// Every token leaving the vault goes through here so payouts, refunds and
+    /// fees together can never exceed what was deposited plus house funding.
+    pub fn record_outflow(&mut self, amount: u64) -> Result<()> {
+        let total_paid_out = self.total_paid_out.checked_add(amount).ok_or(DarkPoolError::Overflow)?;
+        let committed = (total_paid_out as u128) + (self.fees_accrued as u128);
+
+        if committed > self.backing() {
+            msg!(
+                "Solvency violation on market {}: paid out {} + outflow {} + fees {} > deposited {} + house {}",
+                self.market_id,
+                self.total_paid_out,
+                amount,
+                self.fees_accrued,
+                self.total_deposited,
+                self.house_funding
+            );
+            return err!(DarkPoolError::SolvencyViolation);
+        }
+
+        self.total_paid_out = total_paid_out;
+        Ok(())
+    }
+
+    pub fn odds_reveal_due(&self, now: i64) -> bool {
+        self.last_odds_reveal_ts
+            .checked_add(self.odds_reveal_interval)
+            .is_some_and(|next| now >= next)
+    }
+
+    /// Overwrites the oldest snapshot once the history is full.
+    pub fn record_odds_snapshot(&mut self, entry: OddsSnapshotEntry) {
+        let slot = self.odds_snapshot_count as usize % ODDS_SNAPSHOT_HISTORY;
+        self.odds_snapshots[slot] = entry;
+        self.odds_snapshot_count = self.odds_snapshot_count.wrapping_add(1);
+    }
+
+    /// Commitments can be opened from betting close until the resolution deadline.
+    pub fn reveal_window_end(&self) -> i64 {
+        self.resolution_end_ts
+    }
+
+    /// Mirrors the MPC bet validation: an out-of-range reveal stakes nothing.
+    pub fn revealed_stake(&self, outcome: u8, amount: u64, deposit_amount: u64) -> u64 {
+        if outcome > 1 || amount == 0 || amount < self.min_bet || amount > self.max_bet || amount > deposit_amount {
+            0
+        } else {
+            amount
+        }
+    }
+
+    pub fn revealed_payout(&self, outcome: u8, amount: u64) -> Result<u64> {
+        let winning_outcome = self.winning_outcome.ok_or(DarkPoolError::MarketNotResolved)?;
+        let (winning_pool, losing_pool) = if winning_outcome == 1 {
+            (self.revealed_yes_pool, self.revealed_no_pool)
+        } else {
+            (self.revealed_no_pool, self.revealed_yes_pool)
+        };
+
+        // Nobody backed the winner, so every revealed stake comes back with its share of the forfeits
+        if winning_pool == 0 {
+            return Self::pro_rata(amount, self.forfeited_pool as u128, losing_pool);
+        }
+        if outcome != winning_outcome {
+            return Ok(0);
+        }
+
+        // Deposits behind unopened commitments are split among the winners with the losing side
+        let distributable = (losing_pool as u128) + (self.forfeited_pool as u128);
+        Self::pro_rata(amount, distributable, winning_pool)
+    }
+
+    fn pro_rata(amount: u64, distributable: u128, pool: u64) -> Result<u64> {
+        if pool == 0 {
+            return Ok(amount);
+        }
+        let share = (amount as u128)
+            .checked_mul(distributable)
+            .ok_or(DarkPoolError::Overflow)?
+            / pool as u128;
+        let payout = amount.checked_add(share as u64).ok_or(DarkPoolError::Overflow)?;
+        Ok(payout)
+    }
+
+    /// The bet's side is encrypted, so each deposit reserves its winnings at the
+    /// longer of the two odds.
+    pub fn reserve_liability(&mut self, deposit_amount: u64) -> Result<()> {
+        let exposure = PricingMode::exposure(self.pricing_mode.odds(), deposit_amount)?;
+        let house_liability = self.house_liability.checked_add(exposure).ok_or(DarkPoolError::Overflow)?;
+        require!(house_liability <= self.house_funding, DarkPoolError::HouseLiabilityExceeded);
+
+        self.house_liability = house_liability;
+        Ok(())
+    }
+
+    pub fn release_liability(&mut self, deposit_amount: u64, locked_odds: (u32, u32)) -> Result<()> {
+        let exposure = PricingMode::exposure(locked_odds, deposit_amount)?;
+        self.house_liability = self.house_liability.saturating_sub(exposure);
+        Ok(())
+    }
+
+    /// A computed payout swaps the position's reservation for what it actually won. Winnings
+    /// above the stake come out of house funds; a loss frees the reservation entirely.
+    pub fn settle_liability(&mut self, deposit_amount: u64, locked_odds: (u32, u32), payout_amount: u64) -> Result<()> {
+        self.release_liability(deposit_amount, locked_odds)?;
+        if matches!(self.pricing_mode, PricingMode::FixedOdds { .. }) {
+            let winnings = payout_amount.saturating_sub(deposit_amount);
+            self.house_payouts = self.house_payouts.checked_add(winnings).ok_or(DarkPoolError::Overflow)?;
+        }
+        self.unclaimed_payouts = self.unclaimed_payouts.checked_add(payout_amount).ok_or(DarkPoolError::Overflow)?;
+        Ok(())
+    }
+
+    /// An encrypted payout keeps its reservation until the market-wide total is revealed.
+    pub fn record_private_payout(&mut self, deposit_amount: u64, locked_odds: (u32, u32)) -> Result<()> {
+        let exposure = PricingMode::exposure(locked_odds, deposit_amount)?;
+        self.private_exposure = self.private_exposure.checked_add(exposure).ok_or(DarkPoolError::Overflow)?;
+        Ok(())
+    }
+
+    /// Books what the private notes actually pay, revealed with the last one written,
+    /// in place of the reservations held for them.
+    pub fn settle_private_payouts(&mut self, total_payouts: u64, total_winnings: u64) -> Result<()> {
+        self.house_liability = self.house_liability.saturating_sub(self.private_exposure);
+        self.private_exposure = 0;
+        if matches!(self.pricing_mode, PricingMode::FixedOdds { .. }) {
+            self.house_payouts = self.house_payouts.checked_add(total_winnings).ok_or(DarkPoolError::Overflow)?;
+        }
+
+        // Notes drawn from before the total was known have already left the vault
+        let outstanding = total_payouts.saturating_sub(self.private_withdrawn);
+        self.unclaimed_payouts = self.unclaimed_payouts.checked_add(outstanding).ok_or(DarkPoolError::Overflow)?;
+        self.private_payout_total = Some(total_payouts);
+        Ok(())
+    }
+
+    /// Checked before payouts_computed counts the payout in question.
+    pub fn is_last_private_payout(&self) -> bool {
+        self.payouts_computed.saturating_add(1) == self.pooled_positions
+    }
+
+    pub fn record_claim(&mut self, amount: u64) -> Result<()> {
+        self.unclaimed_payouts = self.unclaimed_payouts.saturating_sub(amount);
+        self.record_outflow(amount)
+    }
+
+    pub fn record_private_claim(&mut self, amount: u64) -> Result<()> {
+        if self.private_payout_total.is_some() {
+            return self.record_claim(amount);
+        }
+        self.private_withdrawn = self.private_withdrawn.checked_add(amount).ok_or(DarkPoolError::Overflow)?;
+        self.record_outflow(amount)
+    }
+
+    /// Refunds on a cancelled market never touch house funds, so none of it stays reserved.
+    pub fn free_house_funds(&self) -> u64 {
+        match self.status {
+            MarketStatus::Cancelled => self.house_funding,
+            MarketStatus::Settled => 0,
+            _ => self
+                .house_funding
+                .saturating_sub(self.house_liability)
+                .saturating_sub(self.house_payouts),
+        }
+    }
+
+    /// Once every payout is computed, whatever the vault holds beyond unclaimed payouts
+    /// and uncollected fees belongs to the house.
+    pub fn sweepable_house_funds(&self) -> u64 {
+        let committed = (self.total_paid_out as u128) + (self.fees_accrued as u128) + (self.unclaimed_payouts as u128);
+        self.backing().saturating_sub(committed) as u64
+    }
+
+    pub fn withdraw_house_funding(&mut self, amount: u64) -> Result<()> {
+        require!(amount <= self.free_house_funds(), DarkPoolError::HouseLiabilityExceeded);
+        self.house_funding -= amount;
+
+        let committed = (self.total_paid_out as u128) + (self.fees_accrued as u128);
+        require!(committed <= self.backing(), DarkPoolError::SolvencyViolation);
+        Ok(())
+    }
+
+    fn backing(&self) -> u128 {
+        (self.total_deposited as u128) + (self.house_funding as u128)
+    }
+
+    pub fn allows_position_transfers(&self) -> bool {
+        matches!(
+            self.status,
+            MarketStatus::Open | MarketStatus::BettingClosed | MarketStatus::Resolved
+        )
+    }
+}
+
+impl PricingMode {
+    /// Zero odds tell the payout circuit to fall back to the parimutuel split.
+    pub fn odds(&self) -> (u32, u32) {
+        match self {
+            PricingMode::Parimutuel => (0, 0),
+            PricingMode::FixedOdds { yes_odds_bps, no_odds_bps } => (*yes_odds_bps, *no_odds_bps),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let PricingMode::FixedOdds { yes_odds_bps, no_odds_bps } = self {
+            let valid = |odds: u32| (BPS_DENOMINATOR as u32..=MAX_FIXED_ODDS_BPS).contains(&odds);
+            require!(valid(*yes_odds_bps) && valid(*no_odds_bps), DarkPoolError::InvalidOdds);
+        }
+        Ok(())
+    }
+
+    fn exposure(odds: (u32, u32), deposit_amount: u64) -> Result<u64> {
+        let max_odds = odds.0.max(odds.1) as u64;
+        if max_odds <= BPS_DENOMINATOR {
+            return Ok(0);
+        }
+
+        let exposure = (deposit_amount as u128)
+            .checked_mul((max_odds - BPS_DENOMINATOR) as u128)
+            .ok_or(DarkPoolError::Overflow)?
+            / BPS_DENOMINATOR as u128;
+        u64::try_from(exposure).map_err(|_| error!(DarkPoolError::Overflow))
     }
 }
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use crate::constants::MARKETS_PER_INDEX_PAGE;
+use crate::errors::DarkPoolError;
+use super::MarketStatus;
+
+#[account]
+#[derive(InitSpace)]
+pub struct MarketIndexPage {
+    pub page: u32,
+    #[max_len(MARKETS_PER_INDEX_PAGE)]
+    pub entries: Vec<MarketIndexEntry>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct MarketIndexEntry {
+    pub market: Pubkey,
+    pub status: MarketStatus,
+}
+
+impl MarketIndexPage {
+    pub fn page_for(market_id: u64) -> u32 {
+        (market_id / MARKETS_PER_INDEX_PAGE as u64) as u32
+    }
+
+    pub fn append(&mut self, market: Pubkey) -> Result<()> {
+        require!(self.entries.len() < MARKETS_PER_INDEX_PAGE, DarkPoolError::MarketIndexPageFull);
+        self.entries.push(MarketIndexEntry {
+            market,
+            status: MarketStatus::Created,
+        });
+        Ok(())
+    }
+
+    pub fn set_status(&mut self, market: Pubkey, status: MarketStatus) -> Result<()> {
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.market == market)
+            .ok_or(DarkPoolError::MarketNotIndexed)?;
+        entry.status = status;
+        Ok(())
+    }
+}
@@ -1,7 +1,21 @@
+pub mod audit;
+pub mod claim;
+pub mod listing;
 pub mod market;
+pub mod market_index;
+pub mod pending;
 pub mod pool;
+pub mod portfolio;
 pub mod position;
+pub mod registry;
 
+pub use audit::*;
+pub use claim::*;
+pub use listing::*;
 pub use market::*;
+pub use market_index::*;
+pub use pending::*;
 pub use pool::*;
+pub use portfolio::*;
 pub use position::*;
+pub use registry::*;
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+use crate::constants::MAX_PENDING_BETS;
+use crate::errors::DarkPoolError;
+
+#[account]
+#[derive(InitSpace)]
+pub struct PendingBetQueue {
+    pub market: Pubkey,
+    #[max_len(MAX_PENDING_BETS)]
+    pub positions: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl PendingBetQueue {
+    pub fn push(&mut self, position: Pubkey) -> Result<()> {
+        require!(self.positions.len() < MAX_PENDING_BETS, DarkPoolError::PendingQueueFull);
+        self.positions.push(position);
+        Ok(())
+    }
+
+    pub fn take_batch(&mut self, size: usize) -> Vec<Pubkey> {
+        let count = size.min(self.positions.len());
+        self.positions.drain(..count).collect()
+    }
+}
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
+use solana_sha256_hasher::hashv;
 use crate::constants::ENCRYPTED_STATE_SIZE;
+use crate::errors::DarkPoolError;
 
 #[account]
 #[derive(InitSpace)]
@@ -7,9 +9,11 @@ pub struct EncryptedPoolState {
     pub market: Pubkey,
     #[max_len(ENCRYPTED_STATE_SIZE)]
     pub encrypted_state: Vec<u8>,
+    pub nonce: u128,
     pub state_version: u64,
     pub last_computation_id: u64,
     pub pending_computations: u32,
+    pub batch_computation_id: u64,
     pub last_updated: i64,
     pub bump: u8,
     pub is_initialized: bool,
@@ -19,4 +23,40 @@ impl EncryptedPoolState {
     pub fn is_ready(&self) -> bool {
         self.is_initialized && self.state_version > 0
     }
+
+    pub fn ciphertext(&self, index: usize) -> Result<[u8; 32]> {
+        self.encrypted_state
+            .get(index * 32..(index + 1) * 32)
+            .and_then(|chunk| chunk.try_into().ok())
+            .ok_or_else(|| DarkPoolError::PoolStateNotInitialized.into())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.encrypted_state.is_empty()
+    }
+
+    /// Markets opened without seed liquidity pass zero placeholders until the first bet lands.
+    pub fn ciphertext_or_empty(&self, index: usize) -> Result<[u8; 32]> {
+        if self.is_empty() {
+            return Ok([0u8; 32]);
+        }
+        self.ciphertext(index)
+    }
+
+    pub fn set_encrypted_state(&mut self, nonce: u128, ciphertexts: &[[u8; 32]]) {
+        self.encrypted_state = ciphertexts.iter().flat_map(|chunk| chunk.iter().copied()).collect();
+        self.nonce = nonce;
+    }
+
+    // Chained over the previous commitment so the event history can be replayed and verified
+    pub fn commitment(&self, previous: &[u8; 32]) -> [u8; 32] {
+        hashv(&[
+            previous,
+            self.market.as_ref(),
+            &self.state_version.to_le_bytes(),
+            &self.nonce.to_le_bytes(),
+            &self.encrypted_state,
+        ])
+        .to_bytes()
+    }
 }
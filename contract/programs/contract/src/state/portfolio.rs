@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use crate::constants::{MAX_PORTFOLIO_ENTRIES, PORTFOLIO_SEED};
+use crate::errors::DarkPoolError;
+use super::PositionStatus;
+
+#[account]
+#[derive(InitSpace)]
+pub struct UserPortfolio {
+    pub owner: Pubkey,
+    #[max_len(MAX_PORTFOLIO_ENTRIES)]
+    pub entries: Vec<PortfolioEntry>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct PortfolioEntry {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub status: PositionStatus,
+}
+
+impl UserPortfolio {
+    /// Adds a position, evicting the oldest settled entry when the portfolio is full.
+    pub fn track(&mut self, market: Pubkey, position: Pubkey, status: PositionStatus) -> Result<()> {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.position == position) {
+            entry.status = status;
+            return Ok(());
+        }
+
+        if self.entries.len() >= MAX_PORTFOLIO_ENTRIES {
+            let settled = self
+                .entries
+                .iter()
+                .position(|entry| entry.status.is_settled())
+                .ok_or(DarkPoolError::PortfolioFull)?;
+            self.entries.remove(settled);
+        }
+
+        self.entries.push(PortfolioEntry { market, position, status });
+        Ok(())
+    }
+
+    pub fn untrack(&mut self, position: Pubkey) {
+        self.entries.retain(|entry| entry.position != position);
+    }
+
+    pub fn set_status(&mut self, position: Pubkey, status: PositionStatus) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.position == position) {
+            entry.status = status;
+        }
+    }
+
+    /// Callbacks take the portfolio as a raw account since a position's current
+    /// owner may never have created one.
+    pub fn update_status(info: &AccountInfo, owner: Pubkey, position: Pubkey, status: PositionStatus) -> Result<()> {
+        if *info.owner != crate::ID {
+            return Ok(());
+        }
+
+        let mut data = info.try_borrow_mut_data()?;
+        let mut portfolio = UserPortfolio::try_deserialize(&mut &data[..])?;
+        require_keys_eq!(portfolio.owner, owner, DarkPoolError::InvalidPosition);
+
+        portfolio.set_status(position, status);
+        portfolio.try_serialize(&mut &mut data[..])
+    }
+
+    pub fn address_for(owner: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[PORTFOLIO_SEED, owner.as_ref()], &crate::ID).0
+    }
+
+    /// Positions can change hands through their asset, so a settlement by anyone
+    /// other than the recorded owner drops the entry instead of updating it.
+    pub fn settle(&mut self, position: Pubkey, status: PositionStatus, holder: Pubkey) {
+        if holder == self.owner {
+            self.set_status(position, status);
+        } else {
+            self.untrack(position);
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use anchor_lang::prelude::*;
+use solana_sha256_hasher::hashv;
 use crate::constants::ENCRYPTED_BET_SIZE;
+use crate::errors::DarkPoolError;
+use crate::nft::position_asset_holder;
 
 #[account]
 #[derive(InitSpace)]
@@ -10,29 +13,53 @@ pub struct UserPosition {
     pub encrypted_bet: Vec<u8>,
     pub user_pubkey: [u8; 32],
     pub nonce: u128,
+    pub bet_commitment: Option<[u8; 32]>,
+    pub encrypted_stake: [[u8; 32]; 2],
+    pub stake_nonce: u128,
+    pub kind: PositionKind,
+    pub asset: Pubkey,
+    pub payout_destination: Option<Pubkey>,
+    pub delegate: Option<Pubkey>,
     pub deposit_amount: u64,
+    pub locked_yes_odds_bps: u32,
+    pub locked_no_odds_bps: u32,
     pub payout_amount: u64,
+    pub encrypted_payout: Option<[u8; 32]>,
+    pub payout_nonce: u128,
     pub status: PositionStatus,
     pub computation_id: u64,
+    pub pool_version: u64,
+    pub disclosure_computation_id: u64,
+    pub audited: bool,
+    pub revealed_outcome: Option<u8>,
+    pub revealed_amount: Option<u64>,
     pub bump: u8,
     pub created_at: i64,
     pub processed_at: Option<i64>,
     pub claimed_at: Option<i64>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug, Default)]
 pub enum PositionStatus {
+    #[default]
     Pending,
     Processed,
+    PayoutPending,
     PayoutComputed,
     Claimed,
     Refunded,
+    Withdrawing,
+    Withdrawn,
+    Listed,
+    Transferring,
+    Forfeited,
 }
 
-impl Default for PositionStatus {
-    fn default() -> Self {
-        PositionStatus::Pending
-    }
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug, Default)]
+pub enum PositionKind {
+    #[default]
+    Bettor,
+    CreatorSeed,
 }
 
 impl UserPosition {
@@ -43,4 +70,81 @@ impl UserPosition {
     pub fn can_claim_refund(&self) -> bool {
         self.status == PositionStatus::Processed || self.status == PositionStatus::Pending
     }
+
+    pub fn locked_odds(&self) -> (u32, u32) {
+        (self.locked_yes_odds_bps, self.locked_no_odds_bps)
+    }
+
+    /// Bound to `owner`, the bettor who committed. Transfers move the asset but never rewrite
+    /// `owner`, so a later holder reveals with the original bettor's key and secrets.
+    pub fn commitment_hash(market: &Pubkey, owner: &Pubkey, outcome: u8, amount: u64, salt: &[u8; 32]) -> [u8; 32] {
+        hashv(&[market.as_ref(), owner.as_ref(), &[outcome], &amount.to_le_bytes(), salt]).to_bytes()
+    }
+
+    pub fn can_disclose(&self) -> bool {
+        matches!(
+            self.status,
+            PositionStatus::Processed | PositionStatus::PayoutComputed | PositionStatus::Claimed
+        )
+    }
+
+    /// Statuses whose callback is bound to `computation_id`, which must not be replaced meanwhile.
+    pub fn has_computation_in_flight(&self) -> bool {
+        matches!(
+            self.status,
+            PositionStatus::Pending
+                | PositionStatus::PayoutPending
+                | PositionStatus::Withdrawing
+                | PositionStatus::Transferring
+        )
+    }
+
+    /// Whether the position's stake is still part of the encrypted pool totals.
+    pub fn counts_toward_pool(&self) -> bool {
+        matches!(
+            self.status,
+            PositionStatus::Processed
+                | PositionStatus::PayoutPending
+                | PositionStatus::PayoutComputed
+                | PositionStatus::Claimed
+                | PositionStatus::Listed
+                | PositionStatus::Transferring
+        )
+    }
+
+    pub fn holder(&self, asset: &AccountInfo) -> Result<Pubkey> {
+        require_keys_eq!(asset.key(), self.asset, DarkPoolError::InvalidPositionAsset);
+        position_asset_holder(asset)
+    }
+
+    /// Payout routing belongs to the owner who set it, so a new owner starts without one.
+    pub fn set_owner(&mut self, owner: Pubkey) {
+        if self.owner != owner {
+            self.payout_destination = None;
+            self.delegate = None;
+        }
+        self.owner = owner;
+    }
+
+    /// The holder may claim to their own account or the payout destination; a delegate
+    /// may only claim to the payout destination. Routing set by a previous owner is ignored.
+    pub fn authorize_claim(&self, holder: Pubkey, claimer: Pubkey, destination: Pubkey, destination_owner: Pubkey) -> Result<()> {
+        let routing_active = holder == self.owner;
+        let to_destination = routing_active && self.payout_destination == Some(destination);
+
+        if claimer == holder {
+            require!(to_destination || destination_owner == holder, DarkPoolError::InvalidPayoutDestination);
+        } else {
+            require!(routing_active && self.delegate == Some(claimer), DarkPoolError::Unauthorized);
+            require!(to_destination, DarkPoolError::InvalidPayoutDestination);
+        }
+
+        Ok(())
+    }
+}
+
+impl PositionStatus {
+    pub fn is_settled(&self) -> bool {
+        matches!(self, PositionStatus::Claimed | PositionStatus::Refunded | PositionStatus::Withdrawn | PositionStatus::Forfeited)
+    }
 }
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+use crate::errors::DarkPoolError;
+use super::MarketIndexPage;
+
+#[account]
+#[derive(InitSpace)]
+pub struct MarketRegistry {
+    pub next_market_id: u64,
+    pub index_page: u32,
+    pub bump: u8,
+}
+
+impl MarketRegistry {
+    pub fn allocate_market_id(&mut self) -> Result<u64> {
+        let market_id = self.next_market_id;
+        self.next_market_id = market_id.checked_add(1).ok_or(DarkPoolError::Overflow)?;
+        self.index_page = MarketIndexPage::page_for(self.next_market_id);
+        Ok(market_id)
+    }
+}
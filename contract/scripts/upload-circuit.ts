@@ -36,6 +36,18 @@ async function main() {
   const circuits = [
     { name: "process_bet", file: "build/process_bet.arcis" },
     { name: "compute_payout", file: "build/compute_payout.arcis" },
+    { name: "seed_liquidity", file: "build/seed_liquidity.arcis" },
+    { name: "withdraw_bet", file: "build/withdraw_bet.arcis" },
+    { name: "transfer_position", file: "build/transfer_position.arcis" },
+    { name: "process_bet_batch", file: "build/process_bet_batch.arcis" },
+    { name: "reveal_odds_snapshot", file: "build/reveal_odds_snapshot.arcis" },
+    { name: "compute_private_payout", file: "build/compute_private_payout.arcis" },
+    { name: "compute_payouts_batch", file: "build/compute_payouts_batch.arcis" },
+    { name: "disclose_position", file: "build/disclose_position.arcis" },
+    { name: "reveal_position", file: "build/reveal_position.arcis" },
+    { name: "audit_pool_chunk", file: "build/audit_pool_chunk.arcis" },
+    { name: "attest_pool_audit", file: "build/attest_pool_audit.arcis" },
+    { name: "withdraw_private_claim", file: "build/withdraw_private_claim.arcis" },
   ];
 
   for (const circuit of circuits) {